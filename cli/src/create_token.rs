@@ -1,7 +1,8 @@
 
 use alloy::{primitives::U256, providers::Provider, rpc::types::TransactionRequest};
 use clap::Args;
-use four_meme_sdk::{CreateTokenParams, FourMemeEvent, FourMemeSdk};
+use four_meme_sdk::{CreateTokenParams, SubscriptionOptions, TokenManagerEvent, FourMemeSdk, TradeKind};
+use std::time::Duration;
 use eyre::Result;
 use alloy::{
     signers::{local::PrivateKeySigner, Signer}
@@ -93,38 +94,44 @@ impl CreateTokenArgs {
 
 
 
-        let (_handle, mut rx) = sdk.subscribe_events().await?;
+        let (_handle, mut rx) = sdk.subscribe_events_with(SubscriptionOptions::default()).await?;
 
         tokio::spawn(async move {
             loop {
-                let event = rx.recv().await.unwrap();
-                match event {
-                    FourMemeEvent::TokenPurchase(e) => {
+                let event = match rx.recv().await.unwrap() {
+                    Ok(event) => event,
+                    Err(e) => {
+                        eprintln!("Event subscription error: {}", e);
+                        continue;
+                    }
+                };
+                match event.event {
+                    TokenManagerEvent::TokenPurchase(e) => {
                         println!("TokenPurchase event: token: {:?}, account: {:?}, price: {:?}, amount: {:?}", e.token, e.account, e.price, e.amount);
                     }
-                    FourMemeEvent::TokenSale(e) => {
+                    TokenManagerEvent::TokenSale(e) => {
                         println!("TokenSale event: token: {:?}, account: {:?}, price: {:?}, amount: {:?}", e.token, e.account, e.price, e.amount);
                     }
-                    FourMemeEvent::TokenCreate(e) => {
+                    TokenManagerEvent::TokenCreate(e) => {
                         println!("TokenCreate event: requestId: {:?}, token: {:?}, launchTime: {:?}, name: {:?}", e.requestId, e.token, e.launchTime, e.name);
 
                         let token_info = cloned_sdk.get_token_info_by_id(e.requestId, access_token.clone()).await.unwrap();
                         println!("Token info: {}", serde_json::to_string_pretty(&token_info).unwrap());
                     }
+                    TokenManagerEvent::LiquidityAdded(e) => {
+                        println!("LiquidityAdded event: token: {:?}, offers: {:?}, funds: {:?}", e.base, e.offers, e.funds);
+                    }
+                    _ => {}
                 }
             }
         });
 
 
         println!("Waiting for transaction confirmation...");
-        let receipt = sdk.provider.get_transaction_receipt(tx_hash).await?;
-        while receipt.is_none() {
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-            let receipt = sdk.provider.get_transaction_receipt(tx_hash).await?;
-            if receipt.is_some() {
-                break;
-            }
-        }
+        let confirmed = sdk.pending_trade(tx_hash, TradeKind::Create)
+            .wait(1, Duration::from_secs(120))
+            .await?;
+        println!("Mined in block {:?}", confirmed.receipt.block_number);
 
         tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
 
@@ -0,0 +1,70 @@
+use alloy::{primitives::Address, providers::{DynProvider, Provider, ProviderBuilder}};
+use clap::Args;
+use four_meme_sdk::{ExportFormat, FourMemeSdk, ScanOptions, SubscriptionFilter};
+use eyre::Result;
+use std::{fs::File, io::{self, BufWriter, Write}};
+
+#[derive(Args)]
+pub struct ExportTradesArgs {
+    #[arg(long, default_value = "https://bsc.blockrazor.xyz")]
+    rpc_url: String,
+
+    /// Only trades of this wallet
+    #[arg(short, long)]
+    wallet: Option<Address>,
+
+    /// Only trades of this token
+    #[arg(short, long)]
+    token: Option<Address>,
+
+    #[arg(long)]
+    from_block: u64,
+
+    /// Defaults to the latest block
+    #[arg(long)]
+    to_block: Option<u64>,
+
+    /// csv or jsonl
+    #[arg(short, long, default_value = "csv")]
+    format: ExportFormat,
+
+    /// Output file; stdout when omitted
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Blocks per eth_getLogs request
+    #[arg(long, default_value_t = 5_000)]
+    chunk_size: u64,
+}
+
+impl ExportTradesArgs {
+    pub async fn execute(&self) -> Result<()> {
+        let provider = ProviderBuilder::new().connect_http(self.rpc_url.parse()?);
+        let sdk = FourMemeSdk::new_with_provider(DynProvider::new(provider), None, None).await?;
+
+        let to_block = match self.to_block {
+            Some(to_block) => to_block,
+            None => sdk.provider.get_block_number().await?,
+        };
+
+        let mut filter = SubscriptionFilter::new();
+        if let Some(wallet) = self.wallet {
+            filter = filter.trader(wallet);
+        }
+        if let Some(token) = self.token {
+            filter = filter.token(token);
+        }
+
+        let writer: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(File::create(path)
+                .map_err(|e| eyre::eyre!("Failed to create output file {}: {}", path, e))?),
+            None => Box::new(io::stdout().lock()),
+        };
+
+        let options = ScanOptions::default().with_chunk_size(self.chunk_size);
+        let written = sdk.export_trades(BufWriter::new(writer), self.format, self.from_block, to_block, filter, options).await?;
+        eprintln!("Exported {} trades from blocks {}..={}", written, self.from_block, to_block);
+
+        Ok(())
+    }
+}
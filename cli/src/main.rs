@@ -5,11 +5,13 @@ mod create_token;
 mod buy_token;
 mod sell_token;
 mod export_private_key;
+mod export_trades;
 
 use create_token::CreateTokenArgs;
 use buy_token::BuyTokenArgs;
 use sell_token::SellTokenArgs;
 use export_private_key::ExportPrivateKeyArgs;
+use export_trades::ExportTradesArgs;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -24,7 +26,9 @@ enum Commands {
     CreateToken(CreateTokenArgs),
     ExportPrivateKey(ExportPrivateKeyArgs),
     BuyToken(BuyTokenArgs),
-    SellToken(SellTokenArgs)
+    SellToken(SellTokenArgs),
+    /// Export trade history as CSV or JSON Lines
+    ExportTrades(ExportTradesArgs),
 }
 
 #[tokio::main]
@@ -44,6 +48,9 @@ async fn main() -> Result<()> {
         Commands::ExportPrivateKey(args) => {
             args.execute().await?;
         }
+        Commands::ExportTrades(args) => {
+            args.execute().await?;
+        }
     }
 
     Ok(())
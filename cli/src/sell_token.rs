@@ -1,8 +1,9 @@
 
 use alloy::{primitives::{Address, U256}, providers::Provider};
 use clap::Args;
-use four_meme_sdk::{FourMemeEvent, FourMemeSdk, SellAmapParams};
+use four_meme_sdk::{AggressiveGas, DefaultGas, SubscriptionOptions, TokenManagerEvent, FourMemeSdk, SellAmapParams};
 use eyre::Result;
+use std::time::Duration;
 use alloy::{
     signers::local::PrivateKeySigner
 };
@@ -19,8 +20,16 @@ pub struct SellTokenArgs {
     #[arg(short, long)]
     amount: U256,
 
-    #[arg(short, long)]
+    #[arg(short, long, required_unless_present = "slippage_bps")]
     min_funds: Option<U256>,
+
+    /// Derive min funds from a fresh quote minus this many basis points
+    #[arg(long, conflicts_with = "min_funds")]
+    slippage_bps: Option<u32>,
+
+    /// Outbid the default gas price by this many percent
+    #[arg(long)]
+    gas_bump_pct: Option<u32>,
 }
 
 impl SellTokenArgs {
@@ -41,6 +50,11 @@ impl SellTokenArgs {
             None,
         )?;
 
+        let sdk = match self.gas_bump_pct {
+            Some(bump_pct) => sdk.with_gas_strategy(AggressiveGas::new(DefaultGas, bump_pct)),
+            None => sdk,
+        };
+
         println!("Wallet address: {:?}", signer.address());
 
         let balance = sdk.provider.get_balance(signer.address()).await?;
@@ -66,45 +80,58 @@ impl SellTokenArgs {
             }
         }
 
-        let tx_hash = sdk.sell_token_amap(SellAmapParams {
-            token: self.token,
-            amount: self.amount,
-            min_funds: self.min_funds,
-            from: None,
-            fee_rate: None,
-            fee_recipient: None,
-            origin: None,
-        }, signer.address()).await?;
+        let pending = match self.slippage_bps {
+            Some(slippage_bps) => {
+                let (decision, pending) = sdk.sell_with_slippage(self.token, self.amount, slippage_bps, signer.address()).await?;
+                println!("quote: cost: {:?}, fee: {:?}, min_funds: {:?}", decision.quote.cost, decision.quote.fee, decision.limit);
+                pending
+            }
+            None => sdk.sell_token_amap(SellAmapParams {
+                token: self.token,
+                amount: self.amount,
+                min_funds: self.min_funds,
+                from: None,
+                fee_rate: None,
+                fee_recipient: None,
+                origin: None,
+            }, signer.address()).await?,
+        };
+
+        let tx_hash = pending.tx_hash();
 
-        let (_handle, mut rx) = sdk.subscribe_events().await?;
+        let (_handle, mut rx) = sdk.subscribe_events_with(SubscriptionOptions::default()).await?;
 
         tokio::spawn(async move {
             loop {
-                let event = rx.recv().await.unwrap();
-                match event {
-                    FourMemeEvent::TokenPurchase(e) => {
+                let event = match rx.recv().await.unwrap() {
+                    Ok(event) => event,
+                    Err(e) => {
+                        eprintln!("Event subscription error: {}", e);
+                        continue;
+                    }
+                };
+                match event.event {
+                    TokenManagerEvent::TokenPurchase(e) => {
                         println!("TokenPurchase event: token: {:?}, account: {:?}, price: {:?}, amount: {:?}", e.token, e.account, e.price, e.amount);
                     }
-                    FourMemeEvent::TokenSale(e) => {
+                    TokenManagerEvent::TokenSale(e) => {
                         println!("TokenSale event: token: {:?}, account: {:?}, price: {:?}, amount: {:?}", e.token, e.account, e.price, e.amount);
                     }
-                    FourMemeEvent::TokenCreate(e) => {
+                    TokenManagerEvent::TokenCreate(e) => {
                         println!("TokenCreate event: requestId: {:?}, token: {:?}, launchTime: {:?}, name: {:?}", e.requestId, e.token, e.launchTime, e.name);
                     }
+                    TokenManagerEvent::LiquidityAdded(e) => {
+                        println!("LiquidityAdded event: token: {:?}, offers: {:?}, funds: {:?}", e.base, e.offers, e.funds);
+                    }
+                    _ => {}
                 }
             }
         });
 
 
         println!("Waiting for transaction confirmation...");
-        let receipt = sdk.provider.get_transaction_receipt(tx_hash).await?;
-        while receipt.is_none() {
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-            let receipt = sdk.provider.get_transaction_receipt(tx_hash).await?;
-            if receipt.is_some() {
-                break;
-            }
-        }
+        let confirmed = pending.wait(1, Duration::from_secs(120)).await?;
+        println!("Mined in block {:?}", confirmed.receipt.block_number);
         println!("Transaction confirmed!, tx_hash: {:?}", tx_hash);
 
         Ok(())
@@ -1,4 +1,4 @@
-use alloy::primitives::{address, U256, Address};
+use alloy::primitives::address;
 use four_meme_sdk::FourMemeSdk;
 
 #[tokio::main]
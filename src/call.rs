@@ -0,0 +1,302 @@
+use std::fmt;
+
+use alloy::{
+    primitives::{utils::format_ether, Address, TxHash, U256},
+    providers::Provider,
+    sol_types::SolInterface,
+};
+
+use crate::{FourMemeError, FourMemeSdk, IFourMeme, Result, SellAmapParams, TradeKind};
+
+
+/// A decoded call to any TokenManager function: the `IFourMemeCalls` enum generated from
+/// `four_meme.json`.
+///
+/// Overloads are numbered in ABI order (`sellToken_0`, `sellToken_1`, ...) and each variant wraps
+/// the call struct with the Solidity argument names. Decode with
+/// [`SolInterface::abi_decode`] or [`decode_call`]; `selector()` and `SIGNATURES` come from the
+/// generated bindings. [`sell_params`](Self::sell_params) normalizes the six `sellToken`
+/// overloads into a [`SellAmapParams`].
+pub type FourMemeCall = IFourMeme::IFourMemeCalls;
+
+impl FourMemeCall {
+    /// Solidity signature of the called function, e.g. `sellToken(address,uint256)`.
+    pub fn signature(&self) -> &'static str {
+        Self::signature_by_selector(self.selector()).unwrap_or_default()
+    }
+
+    /// `Buy`, `Sell` or `Create` for trading calls.
+    pub fn trade_kind(&self) -> Option<TradeKind> {
+        match self {
+            Self::buyToken_0(_) | Self::buyToken_1(_) | Self::buyTokenAMAP_0(_) | Self::buyTokenAMAP_1(_) => Some(TradeKind::Buy),
+            Self::sellToken_0(_)
+                | Self::sellToken_1(_)
+                | Self::sellToken_2(_)
+                | Self::sellToken_3(_)
+                | Self::sellToken_4(_)
+                | Self::sellToken_5(_) => Some(TradeKind::Sell),
+            Self::createToken_0(_) | Self::createToken_1(_) => Some(TradeKind::Create),
+            _ => None,
+        }
+    }
+
+    /// The token a call trades or administers; `None` for token creation, where it is not
+    /// known before mining, and for calls about no particular token.
+    pub fn token(&self) -> Option<Address> {
+        match self {
+            Self::buyToken_0(call) => Some(call.token),
+            Self::buyToken_1(call) => Some(call.token),
+            Self::buyTokenAMAP_0(call) => Some(call.token),
+            Self::buyTokenAMAP_1(call) => Some(call.token),
+            Self::addLiquidity(call) => Some(call.tokenAddress),
+            Self::suspendTrading(call) => Some(call.token),
+            Self::_tokenInfos(call) => Some(call.0),
+            Self::_tokenInfoExs(call) => Some(call.0),
+            _ => self.sell_params().map(|params| params.token),
+        }
+    }
+
+    /// Tokens bought or sold, when the call fixes the amount (`buyTokenAMAP` fixes the BNB spent).
+    pub fn token_amount(&self) -> Option<U256> {
+        match self {
+            Self::buyToken_0(call) => Some(call.amount),
+            Self::buyToken_1(call) => Some(call.amount),
+            _ => self.sell_params().map(|params| params.amount),
+        }
+    }
+
+    /// Any `sellToken` overload as the parameters [`FourMemeSdk::build_sell_token_amap_calldata`] takes.
+    pub fn sell_params(&self) -> Option<SellAmapParams> {
+        let params = |origin, token, from, amount, min_funds, fee: Option<(U256, Address)>| SellAmapParams {
+            token,
+            amount,
+            min_funds,
+            origin,
+            from,
+            fee_rate: fee.map(|(rate, _)| rate),
+            fee_recipient: fee.map(|(_, recipient)| recipient),
+        };
+
+        Some(match self {
+            Self::sellToken_0(c) => params(Some(c.origin), c.token, None, c.amount, Some(c.minFunds), Some((c.feeRate, c.feeRecipient))),
+            Self::sellToken_1(c) => params(Some(c.origin), c.token, None, c.amount, Some(c.minFunds), None),
+            Self::sellToken_2(c) => params(Some(c.origin), c.token, None, c.amount, None, None),
+            Self::sellToken_3(c) => params(None, c.token, None, c.amount, Some(c.minFunds), None),
+            Self::sellToken_4(c) => params(Some(c.origin), c.token, Some(c.from), c.amount, Some(c.minFunds), Some((c.feeRate, c.feeRecipient))),
+            Self::sellToken_5(c) => params(None, c.token, None, c.amount, None, None),
+            _ => return None,
+        })
+    }
+
+    /// One line saying what the call does, for support tooling and logs. BNB and token amounts
+    /// are shown in whole units (18 decimals).
+    pub fn describe(&self) -> String {
+        match self {
+            Self::buyToken_0(c) => format!(
+                "Buy {} tokens of {} for {}, paying at most {} BNB", format_ether(c.amount), c.token, c.to, format_ether(c.maxFunds)
+            ),
+            Self::buyToken_1(c) => format!(
+                "Buy {} tokens of {}, paying at most {} BNB", format_ether(c.amount), c.token, format_ether(c.maxFunds)
+            ),
+            Self::buyTokenAMAP_0(c) => format!(
+                "Buy {} with {} BNB for {}, receiving at least {} tokens", c.token, format_ether(c.funds), c.to, format_ether(c.minAmount)
+            ),
+            Self::buyTokenAMAP_1(c) => format!(
+                "Buy {} with {} BNB, receiving at least {} tokens", c.token, format_ether(c.funds), format_ether(c.minAmount)
+            ),
+            Self::createToken_0(c) => format!("Create a token ({} bytes of arguments, {} bytes of signature)", c.args.len(), c.signature.len()),
+            Self::createToken_1(c) => format!("Create a token ({} bytes of arguments)", c.args.len()),
+            Self::addLiquidity(c) => format!("Migrate the liquidity of {} to PancakeSwap", c.tokenAddress),
+            Self::addTemplate(c) => format!(
+                "Add a curve template: quote {}, initial liquidity {}, max raising {}, total supply {}, max offers {}, min trading fee {}",
+                c.quote, c.initialLiquidity, c.maxRaising, c.totalSupply, c.maxOffers, c.minTradingFee
+            ),
+            Self::grantDeployer(c) => format!("Grant the deployer role to {}", c.account),
+            Self::grantOperator(c) => format!("Grant the operator role to {}", c.account),
+            Self::grantRole(c) => format!("Grant role {} to {}", c.role, c.account),
+            Self::initialize_0(_) => "Initialize the contract".to_string(),
+            Self::initialize_1(c) => format!(
+                "Initialize with signer {}, fee recipient {}, token creator {}, referral reward keeper {} and a {} BNB launch fee",
+                c.signer, c.feeRecipient, c.tokenCreator, c.referralRewardKeeper, format_ether(c.launchFee)
+            ),
+            Self::renounceOwnership(_) => "Renounce ownership of the contract".to_string(),
+            Self::renounceRole(c) => format!("{} renounces role {}", c.account, c.role),
+            Self::revokeDeployer(c) => format!("Revoke the deployer role of {}", c.account),
+            Self::revokeOperator(c) => format!("Revoke the operator role of {}", c.account),
+            Self::revokeRole(c) => format!("Revoke role {} of {}", c.role, c.account),
+            Self::setFeeRecipient(c) => format!("Set the fee recipient to {}", c.v),
+            Self::setLaunchFee(c) => format!("Set the launch fee to {} BNB", format_ether(c.v)),
+            Self::setMinTradingFee(c) => format!("Set the minimum trading fee of template {} to {}", c.template, c.v),
+            Self::setReferralRewardRate(c) => format!("Set the referral reward rate to {}", c.v),
+            Self::setRoleAdmin(c) => format!("Make {} the admin role of {}", c.adminRole, c.role),
+            Self::setSigner(c) => format!("Set the API signer to {}", c.newSigner),
+            Self::setTradingFeeRate(c) => format!("Set the trading fee rate to {}", c.v),
+            Self::suspendTrading(c) => match c.value {
+                true => format!("Suspend trading of {}", c.token),
+                false => format!("Resume trading of {}", c.token),
+            },
+            Self::transferOwnership(c) => format!("Transfer ownership of the contract to {}", c.newOwner),
+            Self::upgradeTo(c) => format!("Upgrade the implementation to {}", c.newImplementation),
+            Self::upgradeToAndCall(c) => format!(
+                "Upgrade the implementation to {} and call it with {} bytes", c.newImplementation, c.data.len()
+            ),
+            Self::withdrawERC20(c) => format!("Withdraw {} base units of ERC-20 {} to {}", c.amount, c.token, c.to),
+            Self::withdrawEth(c) => format!("Withdraw {} BNB to {}", format_ether(c.amount), c.to),
+            _ => match self.sell_params() {
+                Some(params) => describe_sell(&params),
+                None => format!("Read {}", self.signature()),
+            },
+        }
+    }
+}
+
+fn describe_sell(params: &SellAmapParams) -> String {
+    let mut description = format!("Sell {} tokens of {}", format_ether(params.amount), params.token);
+    if let Some(from) = params.from {
+        description += &format!(" from {}", from);
+    }
+    match params.min_funds {
+        Some(min_funds) => description += &format!(" for at least {} BNB", format_ether(min_funds)),
+        None => description += " at any price",
+    }
+    if let Some(origin) = params.origin {
+        description += &format!(", origin {}", origin);
+    }
+    if let (Some(fee_rate), Some(fee_recipient)) = (params.fee_rate, params.fee_recipient) {
+        description += &format!(", fee rate {} to {}", fee_rate, fee_recipient);
+    }
+    description
+}
+
+impl fmt::Display for FourMemeCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.describe())
+    }
+}
+
+
+/// Decodes the input of a TokenManager transaction.
+pub fn decode_call(input: &[u8]) -> Result<FourMemeCall> {
+    FourMemeCall::abi_decode(input).ok().ok_or_else(|| match input.get(..4) {
+        Some(selector) => FourMemeError::Abi(format!("Not a TokenManager call: selector 0x{}", alloy::hex::encode(selector))),
+        None => FourMemeError::Abi(format!("Calldata too short: {} bytes", input.len())),
+    })
+}
+
+impl FourMemeSdk {
+    /// Fetches a transaction and decodes its call to the TokenManager.
+    pub async fn decode_call(&self, tx_hash: TxHash) -> Result<FourMemeCall> {
+        use alloy::consensus::Transaction as _;
+
+        let tx = self.provider.get_transaction_by_hash(tx_hash).await?
+            .ok_or_else(|| FourMemeError::Validation(format!("Unknown transaction {}", tx_hash)))?;
+
+        if tx.to() != Some(*self.contract.address()) {
+            return Err(FourMemeError::Validation(format!(
+                "Transaction {} is not sent to the TokenManager {}", tx_hash, self.contract.address()
+            )));
+        }
+
+        decode_call(tx.input())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::{transaction::Recovered, SignableTransaction, TxEnvelope, TxLegacy},
+        primitives::{Signature, TxKind},
+        rpc::types::Transaction,
+        sol_types::SolCall,
+        transports::mock::Asserter,
+    };
+
+    use super::*;
+    use crate::test_utils::mocked_sdk;
+
+    const ETHER: u64 = 1_000_000_000_000_000_000;
+
+    #[test]
+    fn test_decodes_trading_overloads() {
+        let token = Address::repeat_byte(0x44);
+
+        let buy = IFourMeme::buyToken_1Call { token, amount: U256::from(5u64), maxFunds: U256::from(7u64) };
+        let call = decode_call(&buy.abi_encode()).unwrap();
+        assert_eq!(call, FourMemeCall::buyToken_1(buy));
+        assert_eq!((call.trade_kind(), call.token(), call.token_amount()), (Some(TradeKind::Buy), Some(token), Some(U256::from(5u64))));
+        assert_eq!(call.selector(), IFourMeme::buyToken_1Call::SELECTOR);
+        assert_eq!(call.signature(), "buyToken(address,uint256,uint256)");
+
+        let sell = IFourMeme::sellToken_5Call { token, amount: U256::from(3u64) };
+        assert_eq!(decode_call(&sell.abi_encode()).unwrap().trade_kind(), Some(TradeKind::Sell));
+
+        let liquidity = decode_call(&IFourMeme::addLiquidityCall { tokenAddress: token }.abi_encode()).unwrap();
+        assert_eq!((liquidity.trade_kind(), liquidity.token()), (None, Some(token)));
+        assert_eq!(decode_call(&IFourMeme::_tokenCountCall {}.abi_encode()).unwrap().describe(), "Read _tokenCount()");
+
+        assert!(matches!(decode_call(&[0xde, 0xad, 0xbe, 0xef]), Err(FourMemeError::Abi(_))));
+        assert!(matches!(decode_call(&[0xde, 0xad]), Err(FourMemeError::Abi(_))));
+    }
+
+    #[tokio::test]
+    async fn test_sell_overloads_round_trip_through_params() {
+        let sdk = mocked_sdk(Asserter::new()).await;
+        let full = SellAmapParams {
+            token: Address::repeat_byte(0x44),
+            amount: U256::from(ETHER),
+            min_funds: Some(U256::from(ETHER / 2)),
+            origin: Some(U256::from(3u64)),
+            from: Some(Address::repeat_byte(0xaa)),
+            fee_rate: Some(U256::from(100u64)),
+            fee_recipient: Some(Address::repeat_byte(0xbb)),
+        };
+        let variants = [
+            full.clone(),
+            SellAmapParams { from: None, ..full.clone() },
+            SellAmapParams { from: None, fee_rate: None, fee_recipient: None, ..full.clone() },
+            SellAmapParams { from: None, origin: None, fee_rate: None, fee_recipient: None, ..full.clone() },
+            SellAmapParams { from: None, min_funds: None, fee_rate: None, fee_recipient: None, ..full.clone() },
+            SellAmapParams { from: None, origin: None, min_funds: None, fee_rate: None, fee_recipient: None, ..full.clone() },
+        ];
+
+        for params in variants {
+            let calldata = sdk.build_sell_token_amap_calldata(params.clone()).await.unwrap();
+            assert_eq!(decode_call(&calldata).unwrap().sell_params(), Some(params));
+        }
+
+        let call = decode_call(&sdk.build_sell_token_amap_calldata(full).await.unwrap()).unwrap();
+        assert_eq!(
+            call.to_string(),
+            format!(
+                "Sell 1.000000000000000000 tokens of {} from {} for at least 0.500000000000000000 BNB, origin 3, fee rate 100 to {}",
+                Address::repeat_byte(0x44), Address::repeat_byte(0xaa), Address::repeat_byte(0xbb),
+            ),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decode_call_by_hash() {
+        let suspend = IFourMeme::suspendTradingCall { token: Address::repeat_byte(0x44), value: true };
+        let legacy = TxLegacy {
+            to: TxKind::Call(crate::FOUR_MEME_CONTRACT_ADDRESS),
+            input: suspend.abi_encode().into(),
+            ..Default::default()
+        };
+        let signed = legacy.into_signed(Signature::new(U256::from(1u64), U256::from(1u64), false));
+        let tx = Transaction {
+            inner: Recovered::new_unchecked(TxEnvelope::Legacy(signed), Address::repeat_byte(1)),
+            block_hash: None,
+            block_number: Some(10),
+            transaction_index: Some(0),
+            effective_gas_price: None,
+        };
+
+        let asserter = Asserter::new();
+        asserter.push_success(&tx);
+        let call = mocked_sdk(asserter).await.decode_call(TxHash::ZERO).await.unwrap();
+
+        assert_eq!(call, FourMemeCall::suspendTrading(suspend));
+        assert_eq!(call.describe(), format!("Suspend trading of {}", Address::repeat_byte(0x44)));
+    }
+}
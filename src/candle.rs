@@ -0,0 +1,429 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+    future::ready,
+    str::FromStr,
+};
+
+use alloy::primitives::{Address, U256};
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    FourMemeError, FourMemeEvent, FourMemeSdk, LogPosition, Result, ScanOptions, SubscriptionFilter, SubscriptionHandle,
+    SubscriptionOptions, TradeFill, TradeSide,
+};
+
+
+/// Candle width in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CandleInterval(u64);
+
+impl CandleInterval {
+    pub const SECOND: Self = Self(1);
+    pub const MINUTE: Self = Self(60);
+    pub const FIVE_MINUTES: Self = Self(300);
+    pub const HOUR: Self = Self(3_600);
+
+    pub fn from_secs(secs: u64) -> Self {
+        Self(secs.max(1))
+    }
+
+    pub fn secs(&self) -> u64 {
+        self.0
+    }
+
+    /// Start of the candle containing `timestamp`.
+    pub fn open_time(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.0
+    }
+}
+
+/// Parses `1s`, `1m`, `5m`, `1h`, `1d` and so on.
+impl FromStr for CandleInterval {
+    type Err = FourMemeError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || FourMemeError::Validation(format!("Invalid candle interval {:?}", s));
+        let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let count: u64 = s[..split].parse().map_err(|_| invalid())?;
+        let unit = match &s[split..] {
+            "s" => 1,
+            "m" => 60,
+            "h" => 3_600,
+            "d" => 86_400,
+            _ => return Err(invalid()),
+        };
+
+        match count.checked_mul(unit) {
+            Some(secs) if secs > 0 => Ok(Self(secs)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for CandleInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            secs if secs % 86_400 == 0 => write!(f, "{}d", secs / 86_400),
+            secs if secs % 3_600 == 0 => write!(f, "{}h", secs / 3_600),
+            secs if secs % 60 == 0 => write!(f, "{}m", secs / 60),
+            secs => write!(f, "{}s", secs),
+        }
+    }
+}
+
+
+/// OHLCV of one token over one interval. Prices are the `price` reported by the trade events,
+/// quote wei per whole token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Candle {
+    pub token: Address,
+    pub interval: CandleInterval,
+    /// Unix timestamp of the first second of the candle.
+    pub open_time: u64,
+    pub open: U256,
+    pub high: U256,
+    pub low: U256,
+    pub close: U256,
+    /// BNB paid and received, excluding fees.
+    pub volume_bnb: U256,
+    /// Tokens bought and sold.
+    pub volume_tokens: U256,
+    pub fees: U256,
+    pub trades: u64,
+    pub buys: u64,
+    pub sells: u64,
+    /// Distinct buyers and sellers.
+    pub unique_traders: u64,
+}
+
+impl Candle {
+    /// First second after the candle.
+    pub fn close_time(&self) -> u64 {
+        self.open_time + self.interval.secs()
+    }
+}
+
+
+struct OpenCandle {
+    candle: Candle,
+    traders: HashSet<Address>,
+    /// The trades so far, to rebuild the candle when a reorg removes one.
+    fills: Vec<(Option<LogPosition>, TradeFill)>,
+}
+
+impl OpenCandle {
+    fn new(position: Option<LogPosition>, fill: &TradeFill, interval: CandleInterval, open_time: u64) -> Self {
+        let mut open = Self {
+            candle: Candle {
+                token: fill.token,
+                interval,
+                open_time,
+                open: fill.price,
+                high: fill.price,
+                low: fill.price,
+                close: fill.price,
+                volume_bnb: U256::ZERO,
+                volume_tokens: U256::ZERO,
+                fees: U256::ZERO,
+                trades: 0,
+                buys: 0,
+                sells: 0,
+                unique_traders: 0,
+            },
+            traders: HashSet::new(),
+            fills: Vec::new(),
+        };
+        open.add(position, fill);
+        open
+    }
+
+    fn add(&mut self, position: Option<LogPosition>, fill: &TradeFill) {
+        let candle = &mut self.candle;
+        candle.high = candle.high.max(fill.price);
+        candle.low = candle.low.min(fill.price);
+        candle.close = fill.price;
+        candle.volume_bnb = candle.volume_bnb.saturating_add(fill.cost);
+        candle.volume_tokens = candle.volume_tokens.saturating_add(fill.amount);
+        candle.fees = candle.fees.saturating_add(fill.fee);
+        candle.trades += 1;
+        match fill.side {
+            TradeSide::Buy => candle.buys += 1,
+            TradeSide::Sell => candle.sells += 1,
+        }
+        if self.traders.insert(fill.account) {
+            candle.unique_traders += 1;
+        }
+        self.fills.push((position, fill.clone()));
+    }
+
+    /// The candle recomputed without the trade at `position`; `None` once no trade is left.
+    fn without(self, position: LogPosition) -> Option<Self> {
+        let (interval, open_time) = (self.candle.interval, self.candle.open_time);
+        let mut fills = self.fills.into_iter().filter(|(at, _)| *at != Some(position));
+
+        let (first_position, first) = fills.next()?;
+        let mut open = Self::new(first_position, &first, interval, open_time);
+        for (position, fill) in fills {
+            open.add(position, &fill);
+        }
+        Some(open)
+    }
+}
+
+
+/// Aggregates `TokenPurchase` / `TokenSale` events into per-token candles.
+///
+/// Events need `meta.timestamp` (`SubscriptionOptions::with_timestamps(true)` or
+/// [`FourMemeSdk::fill_timestamps`]) and must arrive in chain order; events without a
+/// timestamp and trades older than the token's open candle are ignored. A trade removed by a
+/// reorg is taken out of its open candle, which is recomputed from the remaining trades; candles
+/// already closed are not revised. Intervals without trades produce no candle.
+pub struct CandleBuilder {
+    interval: CandleInterval,
+    open: HashMap<Address, OpenCandle>,
+}
+
+impl CandleBuilder {
+    pub fn new(interval: CandleInterval) -> Self {
+        Self { interval, open: HashMap::new() }
+    }
+
+    /// All candles of `events`, ordered by open time then token.
+    pub fn build<'a>(interval: CandleInterval, events: impl IntoIterator<Item = &'a FourMemeEvent>) -> Vec<Candle> {
+        let mut builder = Self::new(interval);
+        let mut candles: Vec<_> = events.into_iter().filter_map(|event| builder.push(event)).collect();
+        candles.extend(builder.finish());
+        candles.sort_by_key(|candle| (candle.open_time, candle.token));
+        candles
+    }
+
+    pub fn interval(&self) -> CandleInterval {
+        self.interval
+    }
+
+    /// Adds a trade. Returns the token's previous candle once the trade falls into a later interval.
+    pub fn push(&mut self, event: &FourMemeEvent) -> Option<Candle> {
+        let fill = TradeFill::from_event(event)?;
+        let position = event.meta.position();
+        if event.meta.removed {
+            self.remove(fill.token, position?);
+            return None;
+        }
+        let open_time = self.interval.open_time(event.meta.timestamp?);
+
+        match self.open.get_mut(&fill.token) {
+            Some(open) if open.candle.open_time == open_time => {
+                open.add(position, &fill);
+                None
+            }
+            Some(open) if open.candle.open_time > open_time => None,
+            _ => self.open
+                .insert(fill.token, OpenCandle::new(position, &fill, self.interval, open_time))
+                .map(|closed| closed.candle),
+        }
+    }
+
+    fn remove(&mut self, token: Address, position: LogPosition) {
+        let Some(open) = self.open.remove(&token) else {
+            return;
+        };
+        if let Some(open) = open.without(position) {
+            self.open.insert(token, open);
+        }
+    }
+
+    /// The token's candle still being built.
+    pub fn current(&self, token: Address) -> Option<&Candle> {
+        self.open.get(&token).map(|open| &open.candle)
+    }
+
+    /// Closes the candles that ended at or before `timestamp`, for tokens that stopped trading.
+    pub fn close_until(&mut self, timestamp: u64) -> Vec<Candle> {
+        let ended: Vec<_> = self.open.iter()
+            .filter(|(_, open)| open.candle.close_time() <= timestamp)
+            .map(|(token, _)| *token)
+            .collect();
+
+        let mut candles: Vec<_> = ended.into_iter()
+            .filter_map(|token| self.open.remove(&token))
+            .map(|open| open.candle)
+            .collect();
+        candles.sort_by_key(|candle| (candle.open_time, candle.token));
+        candles
+    }
+
+    /// Closes every candle still open.
+    pub fn finish(self) -> Vec<Candle> {
+        let mut candles: Vec<_> = self.open.into_values().map(|open| open.candle).collect();
+        candles.sort_by_key(|candle| (candle.open_time, candle.token));
+        candles
+    }
+}
+
+
+impl FourMemeSdk {
+    /// Candles of the trades in `from_block..=to_block` matching `filter`, ordered by open time
+    /// then token. Block timestamps are fetched for every chunk of the scan.
+    pub async fn candles(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        filter: SubscriptionFilter,
+        interval: CandleInterval,
+        options: ScanOptions,
+    ) -> Result<Vec<Candle>> {
+        let filter = SubscriptionFilter { kinds: BTreeSet::new(), ..filter }.trades();
+        let mut chunks = Box::pin(self.scan_event_chunks(from_block, to_block, filter, options));
+        let mut builder = CandleBuilder::new(interval);
+        let mut candles = Vec::new();
+
+        while let Some(chunk) = chunks.next().await {
+            let mut events = chunk?.events;
+            self.fill_timestamps(&mut events).await?;
+            candles.extend(events.iter().filter_map(|event| builder.push(event)));
+        }
+
+        candles.extend(builder.finish());
+        candles.sort_by_key(|candle| (candle.open_time, candle.token));
+        Ok(candles)
+    }
+
+    /// Live candles: each one is yielded once a later trade shows its interval is over,
+    /// for that token or any other. Candles still open when the subscription ends are dropped.
+    ///
+    /// Subscription errors are passed through; block timestamps are always fetched.
+    pub async fn candle_stream(
+        &self,
+        options: SubscriptionOptions,
+        interval: CandleInterval,
+    ) -> Result<(SubscriptionHandle, impl Stream<Item = Result<Candle>> + Send + 'static)> {
+        let filter = SubscriptionFilter { kinds: BTreeSet::new(), ..options.filter.clone() }.trades();
+        let (handle, events) = self.event_stream(options.with_filter(filter).with_timestamps(true)).await?;
+
+        let candles = events
+            .scan(CandleBuilder::new(interval), |builder, event| {
+                let candles = match event {
+                    Ok(event) => {
+                        let mut candles: Vec<_> = builder.push(&event).into_iter().map(Ok).collect();
+                        if let Some(timestamp) = event.meta.timestamp {
+                            candles.extend(builder.close_until(timestamp).into_iter().map(Ok));
+                        }
+                        candles
+                    }
+                    Err(e) => vec![Err(e)],
+                };
+                ready(Some(stream::iter(candles)))
+            })
+            .flatten();
+
+        Ok((handle, candles))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::TxHash;
+
+    use super::*;
+    use crate::{EventMeta, IFourMeme, TokenManagerEvent};
+
+    const TOKEN: Address = Address::repeat_byte(0x44);
+
+    fn trade(buy: bool, account: u8, price: u64, amount: u64, cost: u64, timestamp: u64) -> FourMemeEvent {
+        let (price, amount, cost, fee) = (U256::from(price), U256::from(amount), U256::from(cost), U256::from(1u64));
+        let (token, account, offers, funds) = (TOKEN, Address::repeat_byte(account), U256::ZERO, U256::ZERO);
+        let event = match buy {
+            true => TokenManagerEvent::TokenPurchase(IFourMeme::TokenPurchase { token, account, price, amount, cost, fee, offers, funds }),
+            false => TokenManagerEvent::TokenSale(IFourMeme::TokenSale { token, account, price, amount, cost, fee, offers, funds }),
+        };
+
+        FourMemeEvent {
+            meta: EventMeta { timestamp: Some(timestamp), tx_hash: Some(TxHash::ZERO), ..Default::default() },
+            event,
+        }
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!("1s".parse::<CandleInterval>().unwrap(), CandleInterval::SECOND);
+        assert_eq!("5m".parse::<CandleInterval>().unwrap(), CandleInterval::FIVE_MINUTES);
+        assert_eq!("1h".parse::<CandleInterval>().unwrap(), CandleInterval::HOUR);
+        assert_eq!(CandleInterval::from_secs(86_400).to_string(), "1d");
+        assert!("0m".parse::<CandleInterval>().is_err());
+        assert!("5x".parse::<CandleInterval>().is_err());
+    }
+
+    #[test]
+    fn test_builds_candles() {
+        let events = [
+            trade(true, 1, 10, 100, 1_000, 60),
+            trade(true, 2, 14, 50, 700, 70),
+            trade(false, 1, 8, 30, 240, 119),
+            trade(true, 3, 9, 10, 90, 185),
+            trade(true, 3, 1, 1, 1, 100), // older than the open candle
+        ];
+
+        let candles = CandleBuilder::build(CandleInterval::MINUTE, &events);
+
+        assert_eq!(candles.len(), 2);
+        let first = &candles[0];
+        assert_eq!((first.open_time, first.close_time()), (60, 120));
+        assert_eq!((first.open, first.high, first.low, first.close), (U256::from(10u64), U256::from(14u64), U256::from(8u64), U256::from(8u64)));
+        assert_eq!((first.volume_bnb, first.volume_tokens, first.fees), (U256::from(1_940u64), U256::from(180u64), U256::from(3u64)));
+        assert_eq!((first.trades, first.buys, first.sells, first.unique_traders), (3, 2, 1, 2));
+        assert_eq!((candles[1].open_time, candles[1].trades), (180, 1));
+    }
+
+    #[test]
+    fn test_incremental_closing() {
+        let mut builder = CandleBuilder::new(CandleInterval::MINUTE);
+
+        assert_eq!(builder.push(&trade(true, 1, 10, 1, 1, 5)), None);
+        assert_eq!(builder.current(TOKEN).unwrap().trades, 1);
+        assert!(builder.close_until(59).is_empty());
+
+        let closed = builder.push(&trade(true, 1, 11, 1, 1, 61)).unwrap();
+        assert_eq!((closed.open_time, closed.close), (0, U256::from(10u64)));
+
+        let mut removed = trade(true, 2, 12, 1, 1, 62);
+        removed.meta.removed = true;
+        assert_eq!(builder.push(&removed), None);
+
+        assert_eq!(builder.close_until(120).iter().map(|c| (c.open_time, c.trades)).collect::<Vec<_>>(), vec![(60, 1)]);
+        assert!(builder.finish().is_empty());
+    }
+
+    #[test]
+    fn test_removed_trade_is_taken_out_of_its_candle() {
+        let at = |mut event: FourMemeEvent, log_index: u64| {
+            event.meta.block_number = Some(7);
+            event.meta.log_index = Some(log_index);
+            event
+        };
+        let high = at(trade(true, 2, 14, 50, 700, 70), 1);
+        let mut builder = CandleBuilder::new(CandleInterval::MINUTE);
+        builder.push(&at(trade(true, 1, 10, 100, 1_000, 60), 0));
+        builder.push(&high);
+        builder.push(&at(trade(false, 1, 8, 30, 240, 80), 2));
+
+        let mut removed = high;
+        removed.meta.removed = true;
+        assert_eq!(builder.push(&removed), None);
+
+        let candle = builder.current(TOKEN).unwrap();
+        assert_eq!((candle.open, candle.high, candle.low, candle.close), (U256::from(10u64), U256::from(10u64), U256::from(8u64), U256::from(8u64)));
+        assert_eq!((candle.volume_bnb, candle.trades, candle.buys, candle.unique_traders), (U256::from(1_240u64), 2, 1, 1));
+
+        // Removing every trade drops the candle.
+        for log_index in [0, 2] {
+            let mut removed = at(trade(true, 1, 1, 1, 1, 60), log_index);
+            removed.meta.removed = true;
+            builder.push(&removed);
+        }
+        assert!(builder.current(TOKEN).is_none());
+    }
+}
@@ -0,0 +1,224 @@
+use alloy::primitives::U256;
+
+use crate::TokenManager3::TokenInfo;
+
+/// Scale used by `calcLastPrice`: quote wei per whole (1e18) token.
+pub(crate) const PRICE_SCALE: U256 = U256::from_limbs([1_000_000_000_000_000_000, 0, 0, 0]);
+
+/// Trading fee rates are expressed in basis points.
+const FEE_DENOMINATOR: U256 = U256::from_limbs([10_000, 0, 0, 0]);
+
+
+/// Local replica of the TokenManager pricing functions.
+///
+/// The on-chain curve is a constant product over virtual reserves: the token side is
+/// `T - sold` and the quote side is `K / (T - sold)`, where `sold = maxOffers - offers`.
+/// Every method mirrors the pure function of the same name on the contract, including
+/// its integer rounding, so quotes can be computed without an `eth_call`.
+///
+/// All methods return `None` where the contract would revert (underflow, division by
+/// zero or buying past the end of the curve).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BondingCurve {
+    pub k: U256,
+    pub t: U256,
+    pub offers: U256,
+    pub funds: U256,
+    pub max_offers: U256,
+    pub max_raising: U256,
+}
+
+impl From<&TokenInfo> for BondingCurve {
+    fn from(ti: &TokenInfo) -> Self {
+        Self {
+            k: ti.K,
+            t: ti.T,
+            offers: ti.offers,
+            funds: ti.funds,
+            max_offers: ti.maxOffers,
+            max_raising: ti.maxRaising,
+        }
+    }
+}
+
+impl From<TokenInfo> for BondingCurve {
+    fn from(ti: TokenInfo) -> Self {
+        Self::from(&ti)
+    }
+}
+
+impl BondingCurve {
+    /// Tokens already sold off the curve.
+    pub fn sold(&self) -> Option<U256> {
+        self.max_offers.checked_sub(self.offers)
+    }
+
+    /// Virtual token reserve `T - sold`.
+    pub fn token_reserve(&self) -> Option<U256> {
+        self.t.checked_sub(self.sold()?)
+    }
+
+    /// Virtual quote reserve `K / (T - sold)`.
+    pub fn quote_reserve(&self) -> Option<U256> {
+        self.k.checked_div(self.token_reserve()?)
+    }
+
+    /// Mirrors `calcBuyCost`: quote needed to buy `amount` tokens, excluding the trading fee.
+    pub fn calc_buy_cost(&self, amount: U256) -> Option<U256> {
+        let reserve = self.token_reserve()?;
+        let after = reserve.checked_sub(amount)?;
+        self.k.checked_div(after)?.checked_sub(self.k.checked_div(reserve)?)
+    }
+
+    /// Mirrors `calcBuyAmount`: tokens received for `funds` of quote, excluding the trading fee.
+    pub fn calc_buy_amount(&self, funds: U256) -> Option<U256> {
+        let reserve = self.token_reserve()?;
+        let quote = self.k.checked_div(reserve)?.checked_add(funds)?;
+        reserve.checked_sub(self.k.checked_div(quote)?)
+    }
+
+    /// Mirrors `calcSellCost`: quote received for selling `amount` tokens, before the trading fee.
+    pub fn calc_sell_cost(&self, amount: U256) -> Option<U256> {
+        let reserve = self.token_reserve()?;
+        let after = reserve.checked_add(amount)?;
+        self.k.checked_div(reserve)?.checked_sub(self.k.checked_div(after)?)
+    }
+
+    /// Mirrors `calcLastPrice`: marginal price in quote wei per whole token.
+    pub fn calc_last_price(&self) -> Option<U256> {
+        let reserve = self.token_reserve()?;
+        self.k
+            .checked_mul(PRICE_SCALE)?
+            .checked_div(reserve)?
+            .checked_div(reserve)
+    }
+
+    /// Mirrors `calcTradingFee` given the manager's fee rate (bps) and the template minimum.
+    pub fn calc_trading_fee(funds: U256, fee_rate_bps: U256, min_trading_fee: U256) -> Option<U256> {
+        let fee = funds.checked_mul(fee_rate_bps)?.checked_div(FEE_DENOMINATOR)?;
+        Some(fee.max(min_trading_fee))
+    }
+
+    /// Curve state after buying `amount` tokens for `cost`, as the contract records it.
+    pub fn after_buy(&self, amount: U256, cost: U256) -> Option<Self> {
+        Some(Self {
+            offers: self.offers.checked_sub(amount)?,
+            funds: self.funds.checked_add(cost)?,
+            ..*self
+        })
+    }
+
+    /// Curve state after selling `amount` tokens for `cost`, as the contract records it.
+    pub fn after_sell(&self, amount: U256, cost: U256) -> Option<Self> {
+        Some(Self {
+            offers: self.offers.checked_add(amount)?,
+            funds: self.funds.checked_sub(cost)?,
+            ..*self
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_fixtures;
+
+    fn ether(v: u64) -> U256 {
+        U256::from(v) * PRICE_SCALE
+    }
+
+    // Default BNB template: 8 BNB virtual quote, 800M tokens on offer, 24 BNB to graduate.
+    fn fresh_curve() -> BondingCurve {
+        let t = ether(1_066_666_666) + U256::from(666_666_666_666_666_666u64);
+        BondingCurve {
+            k: U256::from(8u64) * PRICE_SCALE * t,
+            t,
+            offers: ether(800_000_000),
+            funds: U256::ZERO,
+            max_offers: ether(800_000_000),
+            max_raising: ether(24),
+        }
+    }
+
+    #[test]
+    fn test_full_curve_raises_max_raising() {
+        let curve = fresh_curve();
+        let cost = curve.calc_buy_cost(curve.offers).unwrap();
+
+        // Integer rounding on T keeps this within a few wei of 24 BNB.
+        let diff = cost.abs_diff(curve.max_raising);
+        assert!(diff < U256::from(1_000_000u64), "cost {cost} diff {diff}");
+    }
+
+    #[test]
+    fn test_buy_amount_inverts_buy_cost() {
+        let curve = fresh_curve();
+        let funds = ether(1);
+        let amount = curve.calc_buy_amount(funds).unwrap();
+        let cost = curve.calc_buy_cost(amount).unwrap();
+
+        // Floor division on both sides leaves at most a rounding step of slack.
+        assert!(cost.abs_diff(funds) < U256::from(1_000u64), "cost {cost} funds {funds}");
+    }
+
+    #[test]
+    fn test_sell_after_buy_round_trips() {
+        let curve = fresh_curve();
+        let amount = ether(10_000_000);
+        let cost = curve.calc_buy_cost(amount).unwrap();
+        let after = curve.after_buy(amount, cost).unwrap();
+
+        let proceeds = after.calc_sell_cost(amount).unwrap();
+        assert!(proceeds <= cost);
+        assert!(cost - proceeds <= U256::from(1u64));
+        assert_eq!(after.after_sell(amount, proceeds).unwrap().offers, curve.offers);
+    }
+
+    #[test]
+    fn test_last_price_increases_with_buys() {
+        let curve = fresh_curve();
+        let before = curve.calc_last_price().unwrap();
+        let amount = ether(100_000_000);
+        let after = curve.after_buy(amount, curve.calc_buy_cost(amount).unwrap()).unwrap();
+
+        assert!(after.calc_last_price().unwrap() > before);
+        // 8 BNB / 1.0667B tokens at launch.
+        assert_eq!(before / U256::from(1_000_000_000u64), U256::from(7u64));
+    }
+
+    #[test]
+    fn test_out_of_range_returns_none() {
+        let curve = fresh_curve();
+        assert_eq!(curve.calc_buy_cost(curve.t + U256::from(1u64)), None);
+
+        let broken = BondingCurve { offers: curve.max_offers + U256::from(1u64), ..curve };
+        assert_eq!(broken.calc_last_price(), None);
+    }
+
+    #[test]
+    fn test_trading_fee_respects_minimum() {
+        let fee = BondingCurve::calc_trading_fee(ether(1), U256::from(100u64), U256::ZERO).unwrap();
+        assert_eq!(fee, ether(1) / U256::from(100u64));
+
+        let min = U256::from(1_000u64);
+        assert_eq!(BondingCurve::calc_trading_fee(U256::from(10u64), U256::from(100u64), min), Some(min));
+    }
+
+    #[test]
+    fn test_matches_eth_call_fixtures() {
+        for fixture in curve_fixtures() {
+            let curve = BondingCurve::from(&fixture.token_info);
+            for (amount, cost) in fixture.calc_buy_cost {
+                assert_eq!(curve.calc_buy_cost(amount), Some(cost), "{} calcBuyCost({amount})", fixture.name);
+            }
+            for (amount, cost) in fixture.calc_sell_cost {
+                assert_eq!(curve.calc_sell_cost(amount), Some(cost), "{} calcSellCost({amount})", fixture.name);
+            }
+            for (funds, amount) in fixture.calc_buy_amount {
+                assert_eq!(curve.calc_buy_amount(funds), Some(amount), "{} calcBuyAmount({funds})", fixture.name);
+            }
+            assert_eq!(curve.calc_last_price(), Some(fixture.calc_last_price), "{} calcLastPrice", fixture.name);
+        }
+    }
+}
@@ -1,14 +1,213 @@
+use std::sync::Arc;
+
+use alloy::{
+    primitives::{Bytes, TxHash},
+    providers::PendingTransactionError,
+    transports::{RpcError, TransportError},
+};
 use thiserror::Error;
 
+use crate::ContractRevert;
+
+
+pub type Result<T, E = FourMemeError> = std::result::Result<T, E>;
 
 #[derive(Debug, Error)]
 pub enum FourMemeError {
-    #[error("provider error: {0}")]
-    Provider(String),
-    #[error("contract error: {0}")]
-    Contract(String),
+    /// RPC node unreachable, rate limited or returned a non-revert error.
+    #[error("transport error: {0}")]
+    Transport(TransportError),
+    /// The contract reverted during `eth_call`, `eth_estimateGas` or execution.
+    #[error("contract reverted: {reason}")]
+    Revert {
+        reason: ContractRevert,
+        data: Bytes,
+    },
+    /// `eth_estimateGas` reverted, so the transaction was not sent: it would have failed on-chain.
+    #[error("transaction would revert: {reason}")]
+    EstimationReverted {
+        reason: ContractRevert,
+        data: Bytes,
+    },
+    /// The Four Meme API answered with `code != 0`.
+    #[error("api error {code}: {msg}")]
+    Api { code: i64, msg: String },
+    /// Login, nonce or access token rejected by the Four Meme API.
+    #[error("auth error: {0}")]
+    Auth(String),
+    /// Parameters rejected locally before anything was sent.
+    #[error("validation error: {0}")]
+    Validation(String),
+    /// The transaction was not confirmed within the requested time. It may still be mined:
+    /// wait on it again (`can_wait_again()`) instead of sending the trade a second time.
+    #[error("timed out waiting for transaction {0}")]
+    Timeout(TxHash),
+    /// The node no longer knows the transaction, or its nonce was used by another one.
+    #[error("transaction {0} was dropped or replaced")]
+    Dropped(TxHash),
+    /// The Four Meme API could not be reached or answered with a non-2xx status.
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// A broadcast subscriber fell behind and missed this many events.
+    #[error("subscriber lagged behind and missed {0} events")]
+    Lagged(u64),
+    /// An error delivered to every subscriber of a broadcast.
+    #[error("{0}")]
+    Shared(Arc<FourMemeError>),
     #[error("abi error: {0}")]
     Abi(String),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("csv error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "indexer")]
+    #[error("database error: {0}")]
+    Database(#[from] rusqlite::Error),
     #[error("other: {0}")]
     Other(String),
 }
+
+impl FourMemeError {
+    /// Whether the same request may succeed if sent again unchanged.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Transport(RpcError::ErrorResp(payload)) => payload.is_retry_err(),
+            Self::Transport(RpcError::Transport(kind)) => kind.is_retry_err(),
+            Self::Transport(RpcError::NullResp) => true,
+            Self::Transport(_) => false,
+            Self::Http(e) => e.is_timeout() || e.is_connect() || e.status().is_some_and(|s| s.is_server_error()),
+            Self::Shared(e) => e.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// The transaction is still unconfirmed: waiting on the same hash again may succeed.
+    /// Unlike `is_retryable()`, this never means the request should be re-sent.
+    pub fn can_wait_again(&self) -> bool {
+        matches!(self, Self::Timeout(_))
+    }
+
+    /// The node rejected the transaction because its nonce was already used.
+    pub fn is_nonce_too_low(&self) -> bool {
+        match self {
+            Self::Transport(RpcError::ErrorResp(payload)) => {
+                let msg = payload.message.to_ascii_lowercase();
+                msg.contains("nonce too low") || msg.contains("nonce is too low")
+            }
+            _ => false,
+        }
+    }
+
+    /// The node does not implement the requested method.
+    pub fn is_unsupported_method(&self) -> bool {
+        match self {
+            // -32601: method not found, -32004: method not supported (EIP-1474).
+            Self::Transport(RpcError::ErrorResp(payload)) => matches!(payload.code, -32601 | -32004),
+            _ => false,
+        }
+    }
+
+    pub fn is_revert(&self) -> bool {
+        matches!(self, Self::Revert { .. } | Self::EstimationReverted { .. })
+    }
+
+    /// The decoded revert, if this error is a contract revert.
+    pub fn revert(&self) -> Option<&ContractRevert> {
+        match self {
+            Self::Revert { reason, .. } | Self::EstimationReverted { reason, .. } => Some(reason),
+            _ => None,
+        }
+    }
+}
+
+impl From<TransportError> for FourMemeError {
+    fn from(e: TransportError) -> Self {
+        if let Some(payload) = e.as_error_resp() {
+            if payload.message.contains("revert") {
+                let data = payload.as_revert_data().unwrap_or_default();
+                let reason = match ContractRevert::decode(&data) {
+                    // Some nodes put the reason in the message and omit the data field.
+                    ContractRevert::Empty => payload.message
+                        .split_once(':')
+                        .map(|(_, reason)| ContractRevert::from_reason(reason))
+                        .unwrap_or(ContractRevert::Empty),
+                    reason => reason,
+                };
+                return Self::Revert { reason, data };
+            }
+        }
+
+        Self::Transport(e)
+    }
+}
+
+impl From<alloy::contract::Error> for FourMemeError {
+    fn from(e: alloy::contract::Error) -> Self {
+        match e {
+            alloy::contract::Error::TransportError(e) => e.into(),
+            alloy::contract::Error::PendingTransactionError(e) => e.into(),
+            e => Self::Abi(e.to_string()),
+        }
+    }
+}
+
+impl From<PendingTransactionError> for FourMemeError {
+    fn from(e: PendingTransactionError) -> Self {
+        match e {
+            PendingTransactionError::TransportError(e) => e.into(),
+            e => Self::Other(e.to_string()),
+        }
+    }
+}
+
+impl From<alloy::sol_types::Error> for FourMemeError {
+    fn from(e: alloy::sol_types::Error) -> Self {
+        Self::Abi(e.to_string())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::rpc::json_rpc::ErrorPayload;
+    use alloy::sol_types::{Revert, SolError};
+
+    use super::*;
+
+    fn error_resp(code: i64, message: &str, data: Option<&str>) -> TransportError {
+        let data = data.map(|d| serde_json::value::to_raw_value(d).unwrap());
+        RpcError::ErrorResp(ErrorPayload { code, message: message.to_string().into(), data })
+    }
+
+    #[test]
+    fn test_revert_with_data_is_decoded() {
+        let data = Revert::from("Slippage").abi_encode();
+        let hex = format!("0x{}", alloy::hex::encode(&data));
+
+        let err = FourMemeError::from(error_resp(3, "execution reverted: Slippage", Some(&hex)));
+        match err {
+            FourMemeError::Revert { reason, data: raw } => {
+                assert_eq!(reason, ContractRevert::Slippage("Slippage".to_string()));
+                assert_eq!(raw.as_ref(), data.as_slice());
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_revert_reason_falls_back_to_message() {
+        let err = FourMemeError::from(error_resp(-32000, "execution reverted: Disabled", None));
+        assert_eq!(err.revert(), Some(&ContractRevert::TradingHalted("Disabled".to_string())));
+        assert!(err.to_string().contains("Disabled"));
+    }
+
+    #[test]
+    fn test_rate_limit_is_retryable_transport() {
+        let err = FourMemeError::from(error_resp(429, "too many requests", None));
+        assert!(matches!(err, FourMemeError::Transport(_)));
+        assert!(err.is_retryable());
+        assert!(!FourMemeError::Validation("bad".into()).is_retryable());
+    }
+}
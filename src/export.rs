@@ -0,0 +1,292 @@
+use std::{fmt, io::Write, str::FromStr};
+
+use alloy::primitives::{Address, TxHash, U256};
+use futures::StreamExt;
+use serde::{Serialize, Serializer};
+
+use crate::{FourMemeError, FourMemeEvent, FourMemeSdk, Result, ScanOptions, SubscriptionFilter, TradeFill, TradeSide};
+
+
+/// Column names of [`TradeRecord`], in output order.
+pub const TRADE_COLUMNS: [&str; 14] = [
+    "block_number",
+    "log_index",
+    "timestamp",
+    "tx_hash",
+    "side",
+    "token",
+    "account",
+    "amount",
+    "cost",
+    "fee",
+    "net_funds",
+    "price",
+    "offers",
+    "funds",
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+impl FromStr for ExportFormat {
+    type Err = FourMemeError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "ndjson" | "json-lines" => Ok(Self::JsonLines),
+            _ => Err(FourMemeError::Validation(format!("Unknown export format {:?}, expected csv or jsonl", s))),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::JsonLines => write!(f, "jsonl"),
+        }
+    }
+}
+
+
+fn decimal<S: Serializer>(value: &U256, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// One exported trade. Amounts are decimal wei strings so spreadsheets and JSON parsers keep
+/// every digit; `cost` excludes the fee and `net_funds` is what left (buy) or reached (sell)
+/// the wallet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TradeRecord {
+    pub block_number: Option<u64>,
+    pub log_index: Option<u64>,
+    /// Unix timestamp of the block.
+    pub timestamp: Option<u64>,
+    pub tx_hash: TxHash,
+    pub side: &'static str,
+    pub token: Address,
+    pub account: Address,
+    #[serde(serialize_with = "decimal")]
+    pub amount: U256,
+    #[serde(serialize_with = "decimal")]
+    pub cost: U256,
+    #[serde(serialize_with = "decimal")]
+    pub fee: U256,
+    #[serde(serialize_with = "decimal")]
+    pub net_funds: U256,
+    #[serde(serialize_with = "decimal")]
+    pub price: U256,
+    #[serde(serialize_with = "decimal")]
+    pub offers: U256,
+    #[serde(serialize_with = "decimal")]
+    pub funds: U256,
+}
+
+impl TradeRecord {
+    pub fn from_event(event: &FourMemeEvent) -> Option<Self> {
+        let fill = TradeFill::from_event(event)?;
+
+        Some(Self {
+            block_number: fill.block_number,
+            log_index: event.meta.log_index,
+            timestamp: event.meta.timestamp,
+            tx_hash: fill.tx_hash,
+            side: match fill.side {
+                TradeSide::Buy => "buy",
+                TradeSide::Sell => "sell",
+            },
+            token: fill.token,
+            account: fill.account,
+            amount: fill.amount,
+            cost: fill.cost,
+            fee: fill.fee,
+            net_funds: fill.net_funds(),
+            price: fill.price,
+            offers: fill.offers,
+            funds: fill.funds,
+        })
+    }
+}
+
+
+enum Sink<W: Write> {
+    Csv(Box<csv::Writer<W>>),
+    JsonLines(W),
+}
+
+/// Writes trades one at a time, so exports never hold the whole history in memory.
+///
+/// CSV output always starts with the [`TRADE_COLUMNS`] header, even when no trade follows.
+pub struct TradeWriter<W: Write> {
+    sink: Sink<W>,
+    written: u64,
+}
+
+impl<W: Write> TradeWriter<W> {
+    pub fn new(writer: W, format: ExportFormat) -> Result<Self> {
+        let sink = match format {
+            ExportFormat::Csv => {
+                let mut csv = csv::WriterBuilder::new().has_headers(false).from_writer(writer);
+                csv.write_record(TRADE_COLUMNS)?;
+                Sink::Csv(Box::new(csv))
+            }
+            ExportFormat::JsonLines => Sink::JsonLines(writer),
+        };
+
+        Ok(Self { sink, written: 0 })
+    }
+
+    /// Writes `event` if it is a trade that a reorg did not remove. Returns whether it was written.
+    pub fn write_event(&mut self, event: &FourMemeEvent) -> Result<bool> {
+        if event.meta.removed {
+            return Ok(false);
+        }
+        match TradeRecord::from_event(event) {
+            Some(record) => self.write(&record).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    pub fn write(&mut self, record: &TradeRecord) -> Result<()> {
+        match &mut self.sink {
+            Sink::Csv(csv) => csv.serialize(record)?,
+            Sink::JsonLines(writer) => {
+                serde_json::to_writer(&mut *writer, record)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Trades written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        match &mut self.sink {
+            Sink::Csv(csv) => csv.flush()?,
+            Sink::JsonLines(writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(self) -> Result<W> {
+        match self.sink {
+            Sink::Csv(csv) => csv.into_inner().map_err(|e| e.into_error().into()),
+            Sink::JsonLines(mut writer) => {
+                writer.flush()?;
+                Ok(writer)
+            }
+        }
+    }
+}
+
+
+impl FourMemeSdk {
+    /// Writes the trades in `from_block..=to_block` matching `filter` (for example
+    /// `SubscriptionFilter::new().trader(wallet)` or `.token(token)`), oldest first, with block
+    /// timestamps. Only one scan chunk is held in memory at a time. Returns the number of trades.
+    pub async fn export_trades<W: Write>(
+        &self,
+        writer: W,
+        format: ExportFormat,
+        from_block: u64,
+        to_block: u64,
+        filter: SubscriptionFilter,
+        options: ScanOptions,
+    ) -> Result<u64> {
+        let filter = SubscriptionFilter { kinds: Default::default(), ..filter }.trades();
+        let mut chunks = Box::pin(self.scan_event_chunks(from_block, to_block, filter, options));
+        let mut writer = TradeWriter::new(writer, format)?;
+
+        while let Some(chunk) = chunks.next().await {
+            let mut events = chunk?.events;
+            self.fill_timestamps(&mut events).await?;
+            for event in &events {
+                writer.write_event(event)?;
+            }
+            writer.flush()?;
+        }
+
+        let written = writer.written();
+        writer.into_inner()?;
+        Ok(written)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventMeta, IFourMeme, TokenManagerEvent};
+
+    fn sale() -> FourMemeEvent {
+        FourMemeEvent {
+            meta: EventMeta {
+                block_number: Some(42),
+                log_index: Some(3),
+                timestamp: Some(1_700_000_000),
+                tx_hash: Some(TxHash::repeat_byte(0x11)),
+                ..Default::default()
+            },
+            event: TokenManagerEvent::TokenSale(IFourMeme::TokenSale {
+                token: Address::repeat_byte(0x44),
+                account: Address::repeat_byte(0xaa),
+                price: U256::from(5u64),
+                amount: U256::from(1_000u64),
+                cost: U256::from(100u64),
+                fee: U256::from(1u64),
+                offers: U256::ZERO,
+                funds: U256::from(10u64).pow(U256::from(30u64)),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_csv_has_stable_columns() {
+        let mut writer = TradeWriter::new(Vec::new(), ExportFormat::Csv).unwrap();
+        assert!(writer.write_event(&sale()).unwrap());
+
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines[0], TRADE_COLUMNS.join(","));
+        assert_eq!(
+            lines[1],
+            format!(
+                "42,3,1700000000,{},sell,{:#x},{:#x},1000,100,1,99,5,0,1{}",
+                TxHash::repeat_byte(0x11), Address::repeat_byte(0x44), Address::repeat_byte(0xaa), "0".repeat(30),
+            ),
+        );
+
+        let empty = TradeWriter::new(Vec::new(), ExportFormat::Csv).unwrap().into_inner().unwrap();
+        assert_eq!(String::from_utf8(empty).unwrap().trim_end(), TRADE_COLUMNS.join(","));
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let mut removed = sale();
+        removed.meta.removed = true;
+
+        let mut writer = TradeWriter::new(Vec::new(), ExportFormat::JsonLines).unwrap();
+        writer.write_event(&sale()).unwrap();
+        assert!(!writer.write_event(&removed).unwrap());
+        assert_eq!(writer.written(), 1);
+
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let record: serde_json::Value = serde_json::from_str(output.trim_end()).unwrap();
+        let keys: Vec<_> = record.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys.len(), TRADE_COLUMNS.len());
+        assert_eq!(record["net_funds"], "99");
+        assert_eq!(record["side"], "sell");
+        assert_eq!("jsonl".parse::<ExportFormat>().unwrap(), ExportFormat::JsonLines);
+    }
+}
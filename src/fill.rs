@@ -0,0 +1,191 @@
+use alloy::{
+    primitives::{Address, TxHash, U256},
+    providers::Provider,
+    rpc::types::{Log, TransactionReceipt},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{ConfirmedTrade, FourMemeError, FourMemeEvent, FourMemeSdk, Result, TokenManagerEvent};
+
+
+const BPS: i128 = 10_000;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// What the caller expected before sending the trade, in the same units as the events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeQuote {
+    /// Tokens expected to be bought or sold.
+    pub amount: U256,
+    /// Quote (BNB) expected to be paid or received, excluding the trading fee.
+    pub cost: U256,
+    /// Expected trading fee.
+    pub fee: U256,
+}
+
+/// What actually happened on-chain, decoded from a `TokenPurchase` or `TokenSale` event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeFill {
+    pub tx_hash: TxHash,
+    pub block_number: Option<u64>,
+    pub side: TradeSide,
+    pub token: Address,
+    pub account: Address,
+    /// Tokens bought or sold.
+    pub amount: U256,
+    /// Quote paid (buy) or received (sell), excluding the fee.
+    pub cost: U256,
+    pub fee: U256,
+    /// Last price reported by the event, quote wei per whole token.
+    pub price: U256,
+    /// Curve state after the trade.
+    pub offers: U256,
+    pub funds: U256,
+}
+
+impl TradeFill {
+    /// Decodes the first `TokenPurchase` / `TokenSale` emitted by `contract` in `logs`.
+    pub fn from_logs(logs: &[Log], contract: Address) -> Option<Self> {
+        logs.iter()
+            .filter(|log| log.address() == contract)
+            .find_map(Self::from_log)
+    }
+
+    pub fn from_log(log: &Log) -> Option<Self> {
+        Self::from_event(&FourMemeEvent::decode_log(log)?)
+    }
+
+    pub fn from_event(event: &FourMemeEvent) -> Option<Self> {
+        let (side, token, account, price, amount, cost, fee, offers, funds) = match &event.event {
+            TokenManagerEvent::TokenPurchase(e) => (TradeSide::Buy, e.token, e.account, e.price, e.amount, e.cost, e.fee, e.offers, e.funds),
+            TokenManagerEvent::TokenSale(e) => (TradeSide::Sell, e.token, e.account, e.price, e.amount, e.cost, e.fee, e.offers, e.funds),
+            _ => return None,
+        };
+
+        Some(Self {
+            tx_hash: event.meta.tx_hash.unwrap_or_default(),
+            block_number: event.meta.block_number,
+            side,
+            token,
+            account,
+            amount,
+            cost,
+            fee,
+            price,
+            offers,
+            funds,
+        })
+    }
+
+    pub fn from_receipt(receipt: &TransactionReceipt, contract: Address) -> Option<Self> {
+        Self::from_logs(receipt.logs(), contract)
+    }
+
+    /// BNB that left (buy) or reached (sell) the wallet, fee included.
+    pub fn net_funds(&self) -> U256 {
+        match self.side {
+            TradeSide::Buy => self.cost.saturating_add(self.fee),
+            TradeSide::Sell => self.cost.saturating_sub(self.fee),
+        }
+    }
+
+    /// Realized slippage against `quote` in basis points; positive means worse than quoted.
+    ///
+    /// Buys compare the tokens received, sells compare the quote received.
+    pub fn slippage_bps(&self, quote: &TradeQuote) -> Option<i64> {
+        let (expected, actual) = match self.side {
+            TradeSide::Buy => (quote.amount, self.amount),
+            TradeSide::Sell => (quote.cost, self.cost),
+        };
+
+        if expected.is_zero() {
+            return None;
+        }
+
+        let diff = expected.abs_diff(actual).saturating_mul(U256::from(BPS)) / expected;
+        let diff = i64::try_from(diff).ok()?;
+        Some(if actual > expected { -diff } else { diff })
+    }
+}
+
+impl ConfirmedTrade {
+    /// The buy or sell fill carried by this receipt, if any.
+    pub fn fill(&self) -> Option<TradeFill> {
+        TradeFill::from_event(self.event.as_ref()?)
+    }
+}
+
+impl FourMemeSdk {
+    /// Decodes the `TokenPurchase` / `TokenSale` emitted by a mined transaction.
+    pub async fn fill_from_receipt(&self, tx_hash: TxHash) -> Result<TradeFill> {
+        let receipt = self.provider.get_transaction_receipt(tx_hash).await?
+            .ok_or_else(|| FourMemeError::Validation(format!("No receipt for transaction {}", tx_hash)))?;
+
+        TradeFill::from_receipt(&receipt, *self.contract.address())
+            .ok_or_else(|| FourMemeError::Validation(format!("Transaction {} emitted no TokenPurchase or TokenSale", tx_hash)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::sol_types::SolEvent;
+
+    use super::*;
+    use crate::IFourMeme;
+
+    fn purchase_log(contract: Address) -> Log {
+        let event = IFourMeme::TokenPurchase {
+            token: Address::repeat_byte(0x44),
+            account: Address::repeat_byte(0xaa),
+            price: U256::from(7_500_000_000u64),
+            amount: U256::from(990u64),
+            cost: U256::from(100u64),
+            fee: U256::from(1u64),
+            offers: U256::from(5_000u64),
+            funds: U256::from(300u64),
+        };
+
+        Log {
+            inner: alloy::primitives::Log { address: contract, data: event.encode_log_data() },
+            block_number: Some(42),
+            transaction_hash: Some(TxHash::repeat_byte(7)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fill_from_logs() {
+        let contract = Address::repeat_byte(0x5c);
+        let logs = vec![purchase_log(Address::repeat_byte(0x01)), purchase_log(contract)];
+
+        let fill = TradeFill::from_logs(&logs, contract).unwrap();
+        assert_eq!(fill.side, TradeSide::Buy);
+        assert_eq!(fill.token, Address::repeat_byte(0x44));
+        assert_eq!(fill.net_funds(), U256::from(101u64));
+        assert_eq!(fill.block_number, Some(42));
+        assert_eq!(fill.tx_hash, TxHash::repeat_byte(7));
+
+        assert!(TradeFill::from_logs(&logs[..1], contract).is_none());
+    }
+
+    #[test]
+    fn test_slippage_bps() {
+        let fill = TradeFill::from_log(&purchase_log(Address::ZERO)).unwrap();
+        let quote = |amount: u64| TradeQuote { amount: U256::from(amount), cost: U256::from(100u64), fee: U256::from(1u64) };
+
+        assert_eq!(fill.slippage_bps(&quote(1_000)), Some(100));
+        assert_eq!(fill.slippage_bps(&quote(990)), Some(0));
+        assert_eq!(fill.slippage_bps(&quote(900)), Some(-1_000));
+        assert_eq!(fill.slippage_bps(&quote(0)), None);
+
+        let sell = TradeFill { side: TradeSide::Sell, cost: U256::from(95u64), ..fill };
+        assert_eq!(sell.slippage_bps(&quote(0)), Some(500));
+        assert_eq!(sell.net_funds(), U256::from(94u64));
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+
+use alloy::{
+    primitives::{Address, B256, U256},
+    rpc::types::Filter,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{EventKind, FourMemeEvent, TokenManagerEvent};
+
+
+/// Narrows the TokenManager events delivered by subscriptions and scans.
+///
+/// Empty sets match everything. Event kinds become `topic0` filters on the RPC side; the
+/// TokenManager events index none of their token, account or amount fields, so tokens,
+/// traders and `min_funds` can only be checked client-side after decoding.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionFilter {
+    pub tokens: BTreeSet<Address>,
+    /// Buyers, sellers and creators.
+    pub traders: BTreeSet<Address>,
+    pub kinds: BTreeSet<EventKind>,
+    /// Smallest BNB amount (trade cost or liquidity funds); events that move no BNB pass.
+    pub min_funds: Option<U256>,
+}
+
+impl SubscriptionFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only trades, creation and graduation of `token`.
+    pub fn token(mut self, token: Address) -> Self {
+        self.tokens.insert(token);
+        self
+    }
+
+    pub fn tokens(mut self, tokens: impl IntoIterator<Item = Address>) -> Self {
+        self.tokens.extend(tokens);
+        self
+    }
+
+    pub fn trader(mut self, trader: Address) -> Self {
+        self.traders.insert(trader);
+        self
+    }
+
+    pub fn traders(mut self, traders: impl IntoIterator<Item = Address>) -> Self {
+        self.traders.extend(traders);
+        self
+    }
+
+    pub fn kind(mut self, kind: EventKind) -> Self {
+        self.kinds.insert(kind);
+        self
+    }
+
+    pub fn kinds(mut self, kinds: impl IntoIterator<Item = EventKind>) -> Self {
+        self.kinds.extend(kinds);
+        self
+    }
+
+    /// `TokenPurchase` and `TokenSale` only.
+    pub fn trades(self) -> Self {
+        self.kinds([EventKind::TokenPurchase, EventKind::TokenSale])
+    }
+
+    pub fn min_funds(mut self, min_funds: U256) -> Self {
+        self.min_funds = Some(min_funds);
+        self
+    }
+
+    /// `topic0` values to ask the node for.
+    pub fn signatures(&self) -> Vec<B256> {
+        match self.kinds.is_empty() {
+            true => FourMemeEvent::signatures(),
+            false => self.kinds.iter().map(|kind| kind.signature()).collect(),
+        }
+    }
+
+    /// The RPC-side part of the filter, for `eth_getLogs` and log subscriptions.
+    pub fn log_filter(&self, contract: Address) -> Filter {
+        Filter::new()
+            .address(contract)
+            .event_signature(self.signatures())
+    }
+
+    /// The client-side check, applied after decoding.
+    pub fn matches(&self, event: &TokenManagerEvent) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&event.kind()) {
+            return false;
+        }
+        if !self.tokens.is_empty() && !event.token().is_some_and(|token| self.tokens.contains(&token)) {
+            return false;
+        }
+        if !self.traders.is_empty() && !event.trader().is_some_and(|trader| self.traders.contains(&trader)) {
+            return false;
+        }
+
+        match (self.min_funds, event.funds()) {
+            (Some(min_funds), Some(funds)) => funds >= min_funds,
+            _ => true,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IFourMeme;
+
+    fn purchase(token: u8, account: u8, cost: u64) -> TokenManagerEvent {
+        TokenManagerEvent::TokenPurchase(IFourMeme::TokenPurchase {
+            token: Address::repeat_byte(token),
+            account: Address::repeat_byte(account),
+            price: U256::ZERO,
+            amount: U256::ZERO,
+            cost: U256::from(cost),
+            fee: U256::ZERO,
+            offers: U256::ZERO,
+            funds: U256::ZERO,
+        })
+    }
+
+    #[test]
+    fn test_client_side_matching() {
+        let filter = SubscriptionFilter::new()
+            .token(Address::repeat_byte(1))
+            .trader(Address::repeat_byte(2))
+            .min_funds(U256::from(100u64));
+
+        assert!(filter.matches(&purchase(1, 2, 100)));
+        assert!(!filter.matches(&purchase(1, 2, 99)));
+        assert!(!filter.matches(&purchase(3, 2, 100)));
+        assert!(!filter.matches(&purchase(1, 3, 100)));
+
+        let upgraded = TokenManagerEvent::Upgraded(IFourMeme::Upgraded { implementation: Address::ZERO });
+        assert!(!filter.matches(&upgraded));
+        assert!(SubscriptionFilter::new().min_funds(U256::MAX).matches(&upgraded));
+    }
+
+    #[test]
+    fn test_kinds_become_topic_filters() {
+        let filter = SubscriptionFilter::new().trades();
+        assert_eq!(filter.signatures(), vec![EventKind::TokenPurchase.signature(), EventKind::TokenSale.signature()]);
+        assert!(!filter.matches(&TokenManagerEvent::TradeStop(IFourMeme::TradeStop { token: Address::ZERO })));
+
+        assert_eq!(SubscriptionFilter::new().signatures().len(), EventKind::ALL.len());
+    }
+}
@@ -0,0 +1,254 @@
+use std::sync::Arc;
+
+use alloy::{
+    eips::BlockNumberOrTag,
+    providers::{DynProvider, Provider},
+    rpc::types::TransactionRequest,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+
+/// BSC validators reject anything under 0.05 gwei.
+pub const BSC_MIN_GAS_PRICE_WEI: u128 = 50_000_000;
+
+const GWEI: u128 = 1_000_000_000;
+
+
+/// Fee fields for one transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GasFees {
+    Legacy {
+        gas_price: u128,
+    },
+    Eip1559 {
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    },
+}
+
+impl GasFees {
+    pub fn apply(self, tx: TransactionRequest) -> TransactionRequest {
+        match self {
+            Self::Legacy { gas_price } => tx.gas_price(gas_price),
+            Self::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => tx
+                .max_fee_per_gas(max_fee_per_gas)
+                .max_priority_fee_per_gas(max_priority_fee_per_gas),
+        }
+    }
+
+    /// The most the sender can pay per unit of gas.
+    pub fn max_gas_price(&self) -> u128 {
+        match self {
+            Self::Legacy { gas_price } => *gas_price,
+            Self::Eip1559 { max_fee_per_gas, .. } => *max_fee_per_gas,
+        }
+    }
+
+    /// Scales every fee field by `pct` percent (100 = unchanged).
+    pub fn scale(self, pct: u32) -> Self {
+        let scale = |v: u128| v.saturating_mul(u128::from(pct)) / 100;
+
+        match self {
+            Self::Legacy { gas_price } => Self::Legacy { gas_price: scale(gas_price) },
+            Self::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => Self::Eip1559 {
+                max_fee_per_gas: scale(max_fee_per_gas),
+                max_priority_fee_per_gas: scale(max_priority_fee_per_gas),
+            },
+        }
+    }
+}
+
+
+/// Decides the fee fields of every transaction the SDK builds.
+///
+/// Set one SDK-wide with [`FourMemeSdk::with_gas_strategy`](crate::FourMemeSdk::with_gas_strategy);
+/// since the SDK is cheap to clone, the same call also selects a strategy for a single trade.
+#[async_trait]
+pub trait GasStrategy: Send + Sync {
+    async fn fees(&self, provider: &DynProvider) -> Result<GasFees>;
+}
+
+
+/// Always returns the same fees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedGas(pub GasFees);
+
+#[async_trait]
+impl GasStrategy for FixedGas {
+    async fn fees(&self, _provider: &DynProvider) -> Result<GasFees> {
+        Ok(self.0)
+    }
+}
+
+
+/// Legacy `gasPrice` from `eth_gasPrice`, clamped to the BSC minimum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BscLegacyGas {
+    pub min_gas_price: u128,
+    /// Used when the node does not answer `eth_gasPrice`.
+    pub fallback_gas_price: u128,
+}
+
+impl Default for BscLegacyGas {
+    fn default() -> Self {
+        Self {
+            min_gas_price: BSC_MIN_GAS_PRICE_WEI,
+            fallback_gas_price: 5 * GWEI,
+        }
+    }
+}
+
+#[async_trait]
+impl GasStrategy for BscLegacyGas {
+    async fn fees(&self, provider: &DynProvider) -> Result<GasFees> {
+        let gas_price = provider.get_gas_price().await.unwrap_or(self.fallback_gas_price);
+        Ok(GasFees::Legacy { gas_price: gas_price.max(self.min_gas_price) })
+    }
+}
+
+
+/// EIP-1559 fees from `eth_feeHistory`: the tip is the average reward at `percentile`
+/// over the last `block_count` blocks, and the max fee leaves room for the base fee to
+/// grow by `base_fee_multiplier`. Falls back to a legacy price if the chain reports no base fee.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeHistoryGas {
+    pub block_count: u64,
+    pub percentile: f64,
+    pub base_fee_multiplier: u128,
+    pub min_priority_fee: u128,
+}
+
+impl Default for FeeHistoryGas {
+    fn default() -> Self {
+        Self {
+            block_count: 10,
+            percentile: 50.0,
+            base_fee_multiplier: 2,
+            min_priority_fee: BSC_MIN_GAS_PRICE_WEI,
+        }
+    }
+}
+
+#[async_trait]
+impl GasStrategy for FeeHistoryGas {
+    async fn fees(&self, provider: &DynProvider) -> Result<GasFees> {
+        let history = provider
+            .get_fee_history(self.block_count, BlockNumberOrTag::Latest, &[self.percentile])
+            .await?;
+
+        let base_fee = history.next_block_base_fee().unwrap_or_default();
+        if base_fee == 0 {
+            return BscLegacyGas::default().fees(provider).await;
+        }
+
+        let rewards: Vec<u128> = history.reward.unwrap_or_default()
+            .iter()
+            .filter_map(|r| r.first().copied())
+            .collect();
+        let tip = match rewards.len() {
+            0 => 0,
+            n => rewards.iter().sum::<u128>() / n as u128,
+        };
+        let tip = tip.max(self.min_priority_fee);
+
+        Ok(GasFees::Eip1559 {
+            max_fee_per_gas: base_fee.saturating_mul(self.base_fee_multiplier).saturating_add(tip),
+            max_priority_fee_per_gas: tip,
+        })
+    }
+}
+
+
+/// Outbids another strategy by `bump_pct` percent, for sniping and time-critical exits.
+#[derive(Clone)]
+pub struct AggressiveGas {
+    pub base: Arc<dyn GasStrategy>,
+    pub bump_pct: u32,
+}
+
+impl AggressiveGas {
+    pub fn new(base: impl GasStrategy + 'static, bump_pct: u32) -> Self {
+        Self { base: Arc::new(base), bump_pct }
+    }
+}
+
+impl Default for AggressiveGas {
+    fn default() -> Self {
+        Self::new(DefaultGas, 200)
+    }
+}
+
+#[async_trait]
+impl GasStrategy for AggressiveGas {
+    async fn fees(&self, provider: &DynProvider) -> Result<GasFees> {
+        Ok(self.base.fees(provider).await?.scale(100 + self.bump_pct))
+    }
+}
+
+
+/// Legacy pricing on BSC (56/97), `eth_feeHistory` elsewhere.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefaultGas;
+
+#[async_trait]
+impl GasStrategy for DefaultGas {
+    async fn fees(&self, provider: &DynProvider) -> Result<GasFees> {
+        let chain_id = provider.get_chain_id().await?;
+
+        if chain_id == 56 || chain_id == 97 {
+            BscLegacyGas::default().fees(provider).await
+        } else {
+            FeeHistoryGas::default().fees(provider).await
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::transports::mock::Asserter;
+
+    use super::*;
+    use crate::test_utils::mocked_provider;
+
+    #[tokio::test]
+    async fn test_bsc_legacy_clamps_to_minimum() {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x1");
+        asserter.push_failure_msg("unavailable");
+        let provider = mocked_provider(asserter);
+
+        let strategy = BscLegacyGas::default();
+        assert_eq!(strategy.fees(&provider).await.unwrap(), GasFees::Legacy { gas_price: BSC_MIN_GAS_PRICE_WEI });
+        assert_eq!(strategy.fees(&provider).await.unwrap(), GasFees::Legacy { gas_price: 5 * GWEI });
+    }
+
+    #[tokio::test]
+    async fn test_fee_history_averages_rewards() {
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::json!({
+            "oldestBlock": "0x1",
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00", "0x77359400"],
+            "gasUsedRatio": [0.5, 0.5],
+            "reward": [["0x5f5e100"], ["0x11e1a300"]]
+        }));
+        let provider = mocked_provider(asserter);
+
+        let fees = FeeHistoryGas::default().fees(&provider).await.unwrap();
+        assert_eq!(fees, GasFees::Eip1559 {
+            max_fee_per_gas: 2 * 2 * GWEI + 200_000_000,
+            max_priority_fee_per_gas: 200_000_000,
+        });
+    }
+
+    #[tokio::test]
+    async fn test_aggressive_bumps_base_strategy() {
+        let provider = mocked_provider(Asserter::new());
+        let strategy = AggressiveGas::new(FixedGas(GasFees::Legacy { gas_price: GWEI }), 50);
+
+        assert_eq!(strategy.fees(&provider).await.unwrap(), GasFees::Legacy { gas_price: 3 * GWEI / 2 });
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use alloy::{
+    primitives::Selector,
+    providers::{DynProvider, Provider},
+    rpc::types::TransactionRequest,
+    sol_types::SolCall,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{FourMemeError, IFourMeme, Result, IERC20};
+
+
+/// Limit used for calls with no learned or built-in default.
+const FALLBACK_GAS_LIMIT: u64 = 1_000_000;
+
+
+/// How an `eth_estimateGas` result becomes the gas limit that is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasLimitPolicy {
+    /// Safety margin in percent of the estimate (120 = +20%).
+    pub multiplier_pct: u32,
+    /// Upper bound of the limit; an estimate above it is rejected.
+    pub cap: u64,
+}
+
+impl Default for GasLimitPolicy {
+    fn default() -> Self {
+        Self {
+            multiplier_pct: 120,
+            cap: 5_000_000,
+        }
+    }
+}
+
+impl GasLimitPolicy {
+    pub fn apply(&self, estimate: u64) -> u64 {
+        let limit = u128::from(estimate) * u128::from(self.multiplier_pct) / 100;
+        u64::try_from(limit).unwrap_or(u64::MAX).min(self.cap)
+    }
+}
+
+
+/// Estimates gas limits and remembers what each TokenManager function actually used.
+///
+/// The learned limits are shared by every clone, so all SDK instances built from the
+/// same one benefit from receipts observed by any of them.
+#[derive(Debug, Clone, Default)]
+pub struct GasLimitEstimator {
+    policy: GasLimitPolicy,
+    learned: Arc<RwLock<HashMap<Selector, u64>>>,
+}
+
+impl GasLimitEstimator {
+    pub fn new(policy: GasLimitPolicy) -> Self {
+        Self { policy, learned: Arc::default() }
+    }
+
+    pub fn policy(&self) -> GasLimitPolicy {
+        self.policy
+    }
+
+    /// Same learned limits, different policy.
+    pub fn with_policy(&self, policy: GasLimitPolicy) -> Self {
+        Self { policy, learned: self.learned.clone() }
+    }
+
+    /// Records the gas a mined call with `selector` used; the highest value seen is kept.
+    pub fn record(&self, selector: Selector, gas_used: u64) {
+        let mut learned = self.learned.write().unwrap_or_else(|e| e.into_inner());
+        let entry = learned.entry(selector).or_default();
+        *entry = (*entry).max(gas_used);
+    }
+
+    pub fn learned(&self, selector: Selector) -> Option<u64> {
+        self.learned.read().unwrap_or_else(|e| e.into_inner()).get(&selector).copied()
+    }
+
+    /// Limit used when estimation is unavailable: the learned usage plus margin, else a per-function default.
+    pub fn default_for(&self, selector: Option<Selector>) -> u64 {
+        let Some(selector) = selector else {
+            return FALLBACK_GAS_LIMIT;
+        };
+
+        match self.learned(selector) {
+            Some(gas_used) => self.policy.apply(gas_used),
+            None => builtin_default(selector),
+        }
+    }
+
+    /// `eth_estimateGas` with the policy applied.
+    ///
+    /// A revert fails with `EstimationReverted`. A node without `eth_estimateGas` falls back to
+    /// [`default_for`](Self::default_for); any other failure is returned.
+    pub async fn estimate(&self, provider: &DynProvider, tx: &TransactionRequest) -> Result<u64> {
+        match provider.estimate_gas(tx.clone()).await {
+            Ok(estimate) if estimate > self.policy.cap => Err(FourMemeError::Validation(format!(
+                "Estimated gas {} exceeds the cap {}", estimate, self.policy.cap
+            ))),
+            Ok(estimate) => Ok(self.policy.apply(estimate)),
+            Err(e) => match FourMemeError::from(e) {
+                FourMemeError::Revert { reason, data } => Err(FourMemeError::EstimationReverted { reason, data }),
+                e if e.is_unsupported_method() => Ok(self.default_for(selector(tx))),
+                e => Err(e),
+            },
+        }
+    }
+}
+
+/// The 4-byte function selector of a transaction's calldata.
+pub(crate) fn selector(tx: &TransactionRequest) -> Option<Selector> {
+    tx.input.input()
+        .filter(|input| input.len() >= 4)
+        .map(|input| Selector::from_slice(&input[..4]))
+}
+
+fn builtin_default(selector: Selector) -> u64 {
+    match selector.0 {
+        IFourMeme::buyToken_0Call::SELECTOR
+        | IFourMeme::buyToken_1Call::SELECTOR
+        | IFourMeme::buyTokenAMAP_0Call::SELECTOR
+        | IFourMeme::buyTokenAMAP_1Call::SELECTOR => 400_000,
+        IFourMeme::sellToken_0Call::SELECTOR
+        | IFourMeme::sellToken_1Call::SELECTOR
+        | IFourMeme::sellToken_2Call::SELECTOR
+        | IFourMeme::sellToken_3Call::SELECTOR
+        | IFourMeme::sellToken_4Call::SELECTOR
+        | IFourMeme::sellToken_5Call::SELECTOR => 400_000,
+        IFourMeme::createToken_0Call::SELECTOR | IFourMeme::createToken_1Call::SELECTOR => 3_000_000,
+        IERC20::approveCall::SELECTOR => 100_000,
+        _ => FALLBACK_GAS_LIMIT,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::rpc::json_rpc::ErrorPayload;
+    use alloy::transports::mock::Asserter;
+
+    use super::*;
+    use crate::test_utils::mocked_provider;
+    use crate::ContractRevert;
+
+    fn sell_tx() -> TransactionRequest {
+        TransactionRequest::default().input(IFourMeme::sellToken_0Call::SELECTOR.to_vec().into())
+    }
+
+    #[tokio::test]
+    async fn test_estimate_applies_margin_and_cap() {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x186a0"); // 100_000
+        asserter.push_success(&"0x4c4b40"); // 5_000_000
+        asserter.push_success(&"0x4c4b41");
+        let provider = mocked_provider(asserter);
+
+        let estimator = GasLimitEstimator::default();
+        assert_eq!(estimator.estimate(&provider, &sell_tx()).await.unwrap(), 120_000);
+        assert_eq!(estimator.estimate(&provider, &sell_tx()).await.unwrap(), 5_000_000);
+        assert!(matches!(estimator.estimate(&provider, &sell_tx()).await, Err(FourMemeError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_revert_is_reported() {
+        let asserter = Asserter::new();
+        asserter.push_failure(ErrorPayload {
+            code: 3,
+            message: "execution reverted: Slippage".into(),
+            data: None,
+        });
+        let provider = mocked_provider(asserter);
+
+        let err = GasLimitEstimator::default().estimate(&provider, &sell_tx()).await.unwrap_err();
+        assert!(matches!(err, FourMemeError::EstimationReverted { reason: ContractRevert::Slippage(_), .. }));
+        assert!(err.is_revert());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_falls_back_to_learned_limit() {
+        let unsupported = || ErrorPayload {
+            code: -32601,
+            message: "the method eth_estimateGas does not exist/is not available".into(),
+            data: None,
+        };
+        let asserter = Asserter::new();
+        asserter.push_failure(unsupported());
+        asserter.push_failure(unsupported());
+        let provider = mocked_provider(asserter);
+
+        let estimator = GasLimitEstimator::default();
+        assert_eq!(estimator.estimate(&provider, &sell_tx()).await.unwrap(), 400_000);
+
+        estimator.clone().record(IFourMeme::sellToken_0Call::SELECTOR.into(), 150_000);
+        estimator.record(IFourMeme::sellToken_0Call::SELECTOR.into(), 100_000);
+        assert_eq!(estimator.estimate(&provider, &sell_tx()).await.unwrap(), 180_000);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_failure_is_not_guessed() {
+        let asserter = Asserter::new();
+        asserter.push_failure(ErrorPayload {
+            code: -32000,
+            message: "insufficient funds for gas * price + value".into(),
+            data: None,
+        });
+        let provider = mocked_provider(asserter);
+
+        let err = GasLimitEstimator::default().estimate(&provider, &sell_tx()).await.unwrap_err();
+        assert!(matches!(err, FourMemeError::Transport(_)));
+        // An empty mock queue is a transport failure too.
+        assert!(GasLimitEstimator::default().estimate(&provider, &sell_tx()).await.is_err());
+    }
+}
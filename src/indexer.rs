@@ -0,0 +1,667 @@
+use std::{path::Path, str::FromStr, sync::Mutex};
+
+use alloy::primitives::{Address, TxHash, U256};
+use futures::StreamExt;
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Row, Transaction};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    EventKind, FourMemeEvent, FourMemeSdk, LogPosition, Result, ScanCheckpoint, ScanOptions, SubscriptionFilter,
+    SubscriptionOptions, TokenManagerEvent, TradeFill, TradeSide,
+};
+
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS tokens (
+    address       TEXT PRIMARY KEY,
+    creator       TEXT NOT NULL,
+    request_id    TEXT NOT NULL,
+    name          TEXT NOT NULL,
+    symbol        TEXT NOT NULL,
+    total_supply  TEXT NOT NULL,
+    launch_time   INTEGER NOT NULL,
+    launch_fee    TEXT NOT NULL,
+    block_number  INTEGER NOT NULL,
+    log_index     INTEGER NOT NULL,
+    tx_hash       TEXT NOT NULL,
+    timestamp     INTEGER
+);
+CREATE INDEX IF NOT EXISTS tokens_creator ON tokens (creator);
+
+CREATE TABLE IF NOT EXISTS trades (
+    block_number  INTEGER NOT NULL,
+    log_index     INTEGER NOT NULL,
+    tx_hash       TEXT NOT NULL,
+    timestamp     INTEGER,
+    side          TEXT NOT NULL,
+    token         TEXT NOT NULL,
+    account       TEXT NOT NULL,
+    amount        TEXT NOT NULL,
+    cost          TEXT NOT NULL,
+    fee           TEXT NOT NULL,
+    price         TEXT NOT NULL,
+    offers        TEXT NOT NULL,
+    funds         TEXT NOT NULL,
+    PRIMARY KEY (block_number, log_index)
+);
+CREATE INDEX IF NOT EXISTS trades_token ON trades (token, block_number, log_index);
+CREATE INDEX IF NOT EXISTS trades_account ON trades (account, block_number, log_index);
+
+CREATE TABLE IF NOT EXISTS graduations (
+    token             TEXT PRIMARY KEY,
+    stopped_block     INTEGER,
+    stopped_tx        TEXT,
+    liquidity_block   INTEGER,
+    liquidity_tx      TEXT,
+    quote             TEXT,
+    offers            TEXT,
+    funds             TEXT
+);
+
+CREATE TABLE IF NOT EXISTS checkpoints (
+    name            TEXT PRIMARY KEY,
+    next_block      INTEGER NOT NULL,
+    last_block      INTEGER,
+    last_log_index  INTEGER
+);
+";
+
+/// Recent blocks whose hashes [`Indexer::follow`] re-checks when the options leave `reorg_depth` at 0.
+pub const DEFAULT_REORG_DEPTH: u64 = 15;
+
+/// The events the indexer stores.
+const INDEXED_KINDS: [EventKind; 5] = [
+    EventKind::TokenCreate,
+    EventKind::TokenPurchase,
+    EventKind::TokenSale,
+    EventKind::LiquidityAdded,
+    EventKind::TradeStop,
+];
+
+
+/// A token as created by `TokenCreate`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedToken {
+    pub address: Address,
+    pub creator: Address,
+    pub request_id: U256,
+    pub name: String,
+    pub symbol: String,
+    pub total_supply: U256,
+    pub launch_time: u64,
+    pub launch_fee: U256,
+    pub block_number: u64,
+    pub tx_hash: TxHash,
+    pub timestamp: Option<u64>,
+}
+
+/// A `TokenPurchase` or `TokenSale` with its position in the chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedTrade {
+    pub fill: TradeFill,
+    pub log_index: u64,
+    pub timestamp: Option<u64>,
+}
+
+/// The end of a token's bonding curve: `TradeStop`, then `LiquidityAdded` once it is on PancakeSwap.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Graduation {
+    pub token: Address,
+    pub stopped_block: Option<u64>,
+    pub stopped_tx: Option<TxHash>,
+    pub liquidity_block: Option<u64>,
+    pub liquidity_tx: Option<TxHash>,
+    /// Quote token paired on PancakeSwap; the zero address is BNB.
+    pub quote: Option<Address>,
+    pub offers: Option<U256>,
+    pub funds: Option<U256>,
+}
+
+
+/// Persists tokens, trades and graduations into SQLite.
+///
+/// Ingesting is idempotent: rows are keyed by their log position, so re-ingesting a range
+/// after a crash or overlapping backfills changes nothing. Events flagged `meta.removed` by a
+/// reorg delete what they had stored. Calls block on SQLite; keep the indexer on a runtime
+/// that tolerates short blocking, or wrap it in `spawn_blocking`.
+#[cfg_attr(docsrs, doc(cfg(feature = "indexer")))]
+pub struct Indexer {
+    conn: Mutex<Connection>,
+}
+
+impl Indexer {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    pub fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// The subscription filter selecting the events the indexer stores.
+    pub fn filter() -> SubscriptionFilter {
+        SubscriptionFilter::new().kinds(INDEXED_KINDS)
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Stores `events` in one transaction. Returns how many rows changed.
+    pub fn ingest(&self, events: &[FourMemeEvent]) -> Result<usize> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let changed = ingest_events(&tx, events)?;
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    /// Stores `events` and moves checkpoint `name` in the same transaction.
+    pub fn ingest_with_checkpoint(&self, events: &[FourMemeEvent], name: &str, checkpoint: ScanCheckpoint) -> Result<usize> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let changed = ingest_events(&tx, events)?;
+        save_checkpoint(&tx, name, checkpoint)?;
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    pub fn checkpoint(&self, name: &str) -> Result<Option<ScanCheckpoint>> {
+        checkpoint(&self.conn(), name)
+    }
+
+    pub fn save_checkpoint(&self, name: &str, checkpoint: ScanCheckpoint) -> Result<()> {
+        save_checkpoint(&self.conn(), name, checkpoint)
+    }
+
+    pub fn token(&self, address: Address) -> Result<Option<IndexedToken>> {
+        let token = self.conn()
+            .query_row(&format!("{} WHERE address = ?1", SELECT_TOKENS), params![address.to_string()], token_from_row)
+            .optional()?;
+        Ok(token)
+    }
+
+    /// Tokens launched by `creator`, oldest first.
+    pub fn tokens_created_by(&self, creator: Address) -> Result<Vec<IndexedToken>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!("{} WHERE creator = ?1 ORDER BY block_number, log_index", SELECT_TOKENS))?;
+        let tokens = stmt.query_map(params![creator.to_string()], token_from_row)?.collect::<rusqlite::Result<_>>()?;
+        Ok(tokens)
+    }
+
+    /// Trades of `token`, oldest first.
+    pub fn trades_for_token(&self, token: Address) -> Result<Vec<IndexedTrade>> {
+        self.trades_where("token = ?1", token)
+    }
+
+    /// Trades made by `account`, oldest first.
+    pub fn trades_by_account(&self, account: Address) -> Result<Vec<IndexedTrade>> {
+        self.trades_where("account = ?1", account)
+    }
+
+    fn trades_where(&self, condition: &str, address: Address) -> Result<Vec<IndexedTrade>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!("{} WHERE {} ORDER BY block_number, log_index", SELECT_TRADES, condition))?;
+        let trades = stmt.query_map(params![address.to_string()], trade_from_row)?.collect::<rusqlite::Result<_>>()?;
+        Ok(trades)
+    }
+
+    pub fn graduation(&self, token: Address) -> Result<Option<Graduation>> {
+        let graduation = self.conn()
+            .query_row(&format!("{} WHERE token = ?1", SELECT_GRADUATIONS), params![token.to_string()], graduation_from_row)
+            .optional()?;
+        Ok(graduation)
+    }
+
+    /// Indexes `from_block..=to_block` with [`FourMemeSdk::scan_event_chunks`], resuming from
+    /// checkpoint `name` if it is further along. Returns the checkpoint after the last block.
+    pub async fn backfill(
+        &self,
+        sdk: &FourMemeSdk,
+        name: &str,
+        from_block: u64,
+        to_block: u64,
+        options: ScanOptions,
+    ) -> Result<ScanCheckpoint> {
+        let mut checkpoint = match self.checkpoint(name)? {
+            Some(saved) if saved.next_block > from_block => saved,
+            _ => ScanCheckpoint::at_block(from_block),
+        };
+
+        let mut chunks = Box::pin(sdk.scan_event_chunks(from_block, to_block, Self::filter(), options.resume_from(checkpoint)));
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            checkpoint = chunk.checkpoint();
+            self.ingest_with_checkpoint(&chunk.events, name, checkpoint)?;
+        }
+
+        Ok(checkpoint)
+    }
+
+    /// Indexes new events as they arrive, saving checkpoint `name` after each one and resuming
+    /// from it on the next call. A removed event moves the checkpoint back to where it was, so
+    /// a restart fetches the replacing block again. A `reorg_depth` of 0 is raised to
+    /// [`DEFAULT_REORG_DEPTH`], since the index would otherwise keep the events of replaced blocks.
+    ///
+    /// Returns when the subscription ends or with the first RPC or database error; calling
+    /// again resumes from the checkpoint.
+    pub async fn follow(&self, sdk: &FourMemeSdk, name: &str, mut options: SubscriptionOptions) -> Result<()> {
+        if options.reorg_depth == 0 {
+            options = options.with_reorg_depth(DEFAULT_REORG_DEPTH);
+        }
+        let options = match self.checkpoint(name)? {
+            Some(checkpoint) => options.from_block(checkpoint.next_block),
+            None => options,
+        };
+
+        let (handle, events) = sdk.event_stream(options.with_filter(Self::filter())).await?;
+        let mut events = Box::pin(events);
+        while let Some(event) = events.next().await {
+            if let Err(e) = event.and_then(|event| self.ingest_followed(&event, name)) {
+                handle.cancel();
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn ingest_followed(&self, event: &FourMemeEvent, name: &str) -> Result<usize> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let changed = ingest_events(&tx, std::slice::from_ref(event))?;
+
+        let checkpoint = match event.meta.removed {
+            false => ScanCheckpoint::after(event),
+            true => match (ScanCheckpoint::before(event), checkpoint(&tx, name)?) {
+                (Some(before), Some(current)) if !precedes(before, current) => None,
+                (before, _) => before,
+            },
+        };
+        if let Some(checkpoint) = checkpoint {
+            save_checkpoint(&tx, name, checkpoint)?;
+        }
+
+        tx.commit()?;
+        Ok(changed)
+    }
+}
+
+
+/// Whether resuming from `a` processes events that `b` would skip.
+fn precedes(a: ScanCheckpoint, b: ScanCheckpoint) -> bool {
+    let key = |c: ScanCheckpoint| (c.next_block, c.last.map_or(0, |last| last.log_index + 1));
+    key(a) < key(b)
+}
+
+fn checkpoint(conn: &Connection, name: &str) -> Result<Option<ScanCheckpoint>> {
+    let checkpoint = conn
+        .query_row(
+            "SELECT next_block, last_block, last_log_index FROM checkpoints WHERE name = ?1",
+            params![name],
+            |row| {
+                let last = match (row.get::<_, Option<u64>>(1)?, row.get::<_, Option<u64>>(2)?) {
+                    (Some(block_number), Some(log_index)) => Some(LogPosition { block_number, log_index }),
+                    _ => None,
+                };
+                Ok(ScanCheckpoint { next_block: row.get(0)?, last })
+            },
+        )
+        .optional()?;
+    Ok(checkpoint)
+}
+
+fn save_checkpoint(conn: &Connection, name: &str, checkpoint: ScanCheckpoint) -> Result<()> {
+    conn.execute(
+        "INSERT INTO checkpoints (name, next_block, last_block, last_log_index) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (name) DO UPDATE SET next_block = excluded.next_block, last_block = excluded.last_block,
+             last_log_index = excluded.last_log_index",
+        params![
+            name,
+            checkpoint.next_block,
+            checkpoint.last.map(|last| last.block_number),
+            checkpoint.last.map(|last| last.log_index),
+        ],
+    )?;
+    Ok(())
+}
+
+fn ingest_events(tx: &Transaction, events: &[FourMemeEvent]) -> Result<usize> {
+    let mut changed = 0;
+
+    for event in events {
+        let Some(position) = event.meta.position() else {
+            continue;
+        };
+        let tx_hash = event.meta.tx_hash.unwrap_or_default().to_string();
+
+        changed += match (&event.event, event.meta.removed) {
+            (TokenManagerEvent::TokenPurchase(_) | TokenManagerEvent::TokenSale(_), true) => tx.execute(
+                "DELETE FROM trades WHERE block_number = ?1 AND log_index = ?2",
+                params![position.block_number, position.log_index],
+            )?,
+            (TokenManagerEvent::TokenPurchase(_) | TokenManagerEvent::TokenSale(_), false) => {
+                let Some(fill) = TradeFill::from_event(event) else {
+                    continue;
+                };
+                let side = match fill.side {
+                    TradeSide::Buy => "buy",
+                    TradeSide::Sell => "sell",
+                };
+                tx.execute(
+                    "INSERT OR IGNORE INTO trades (block_number, log_index, tx_hash, timestamp, side, token, account,
+                         amount, cost, fee, price, offers, funds)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                    params![
+                        position.block_number,
+                        position.log_index,
+                        tx_hash,
+                        event.meta.timestamp,
+                        side,
+                        fill.token.to_string(),
+                        fill.account.to_string(),
+                        fill.amount.to_string(),
+                        fill.cost.to_string(),
+                        fill.fee.to_string(),
+                        fill.price.to_string(),
+                        fill.offers.to_string(),
+                        fill.funds.to_string(),
+                    ],
+                )?
+            }
+            (TokenManagerEvent::TokenCreate(e), true) => tx.execute(
+                "DELETE FROM tokens WHERE address = ?1 AND block_number = ?2 AND log_index = ?3",
+                params![e.token.to_string(), position.block_number, position.log_index],
+            )?,
+            (TokenManagerEvent::TokenCreate(e), false) => tx.execute(
+                "INSERT OR IGNORE INTO tokens (address, creator, request_id, name, symbol, total_supply, launch_time,
+                     launch_fee, block_number, log_index, tx_hash, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    e.token.to_string(),
+                    e.creator.to_string(),
+                    e.requestId.to_string(),
+                    e.name,
+                    e.symbol,
+                    e.totalSupply.to_string(),
+                    u64::try_from(e.launchTime).unwrap_or(u64::MAX),
+                    e.launchFee.to_string(),
+                    position.block_number,
+                    position.log_index,
+                    tx_hash,
+                    event.meta.timestamp,
+                ],
+            )?,
+            (TokenManagerEvent::TradeStop(e), true) => tx.execute(
+                "UPDATE graduations SET stopped_block = NULL, stopped_tx = NULL WHERE token = ?1 AND stopped_block = ?2",
+                params![e.token.to_string(), position.block_number],
+            )?,
+            (TokenManagerEvent::TradeStop(e), false) => tx.execute(
+                "INSERT INTO graduations (token, stopped_block, stopped_tx) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (token) DO UPDATE SET stopped_block = excluded.stopped_block, stopped_tx = excluded.stopped_tx
+                 WHERE stopped_block IS NOT excluded.stopped_block",
+                params![e.token.to_string(), position.block_number, tx_hash],
+            )?,
+            (TokenManagerEvent::LiquidityAdded(e), true) => tx.execute(
+                "UPDATE graduations SET liquidity_block = NULL, liquidity_tx = NULL, quote = NULL, offers = NULL, funds = NULL
+                 WHERE token = ?1 AND liquidity_block = ?2",
+                params![e.base.to_string(), position.block_number],
+            )?,
+            (TokenManagerEvent::LiquidityAdded(e), false) => tx.execute(
+                "INSERT INTO graduations (token, liquidity_block, liquidity_tx, quote, offers, funds) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (token) DO UPDATE SET liquidity_block = excluded.liquidity_block,
+                     liquidity_tx = excluded.liquidity_tx, quote = excluded.quote, offers = excluded.offers,
+                     funds = excluded.funds
+                 WHERE liquidity_block IS NOT excluded.liquidity_block",
+                params![
+                    e.base.to_string(),
+                    position.block_number,
+                    tx_hash,
+                    e.quote.to_string(),
+                    e.offers.to_string(),
+                    e.funds.to_string(),
+                ],
+            )?,
+            _ => 0,
+        };
+    }
+
+    Ok(changed)
+}
+
+
+const SELECT_TOKENS: &str = "SELECT address, creator, request_id, name, symbol, total_supply, launch_time, launch_fee,
+    block_number, tx_hash, timestamp FROM tokens";
+
+const SELECT_TRADES: &str = "SELECT block_number, log_index, tx_hash, timestamp, side, token, account, amount, cost, fee,
+    price, offers, funds FROM trades";
+
+const SELECT_GRADUATIONS: &str = "SELECT token, stopped_block, stopped_tx, liquidity_block, liquidity_tx, quote, offers,
+    funds FROM graduations";
+
+/// Reads a column stored as text with `to_string()`.
+fn parsed<T>(row: &Row, index: usize) -> rusqlite::Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    row.get::<_, String>(index)?
+        .parse()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+fn parsed_opt<T>(row: &Row, index: usize) -> rusqlite::Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match row.get::<_, Option<String>>(index)? {
+        Some(_) => parsed(row, index).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn token_from_row(row: &Row) -> rusqlite::Result<IndexedToken> {
+    Ok(IndexedToken {
+        address: parsed(row, 0)?,
+        creator: parsed(row, 1)?,
+        request_id: parsed(row, 2)?,
+        name: row.get(3)?,
+        symbol: row.get(4)?,
+        total_supply: parsed(row, 5)?,
+        launch_time: row.get(6)?,
+        launch_fee: parsed(row, 7)?,
+        block_number: row.get(8)?,
+        tx_hash: parsed(row, 9)?,
+        timestamp: row.get(10)?,
+    })
+}
+
+fn trade_from_row(row: &Row) -> rusqlite::Result<IndexedTrade> {
+    let side = match row.get::<_, String>(4)?.as_str() {
+        "buy" => TradeSide::Buy,
+        _ => TradeSide::Sell,
+    };
+
+    Ok(IndexedTrade {
+        fill: TradeFill {
+            tx_hash: parsed(row, 2)?,
+            block_number: Some(row.get(0)?),
+            side,
+            token: parsed(row, 5)?,
+            account: parsed(row, 6)?,
+            amount: parsed(row, 7)?,
+            cost: parsed(row, 8)?,
+            fee: parsed(row, 9)?,
+            price: parsed(row, 10)?,
+            offers: parsed(row, 11)?,
+            funds: parsed(row, 12)?,
+        },
+        log_index: row.get(1)?,
+        timestamp: row.get(3)?,
+    })
+}
+
+fn graduation_from_row(row: &Row) -> rusqlite::Result<Graduation> {
+    Ok(Graduation {
+        token: parsed(row, 0)?,
+        stopped_block: row.get(1)?,
+        stopped_tx: parsed_opt(row, 2)?,
+        liquidity_block: row.get(3)?,
+        liquidity_tx: parsed_opt(row, 4)?,
+        quote: parsed_opt(row, 5)?,
+        offers: parsed_opt(row, 6)?,
+        funds: parsed_opt(row, 7)?,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        rpc::types::Log,
+        sol_types::SolEvent,
+        transports::mock::Asserter,
+    };
+
+    use super::*;
+    use crate::test_utils::mocked_sdk;
+    use crate::IFourMeme;
+
+    const TOKEN: Address = Address::repeat_byte(0x44);
+    const CREATOR: Address = Address::repeat_byte(0xcc);
+
+    fn log(data: alloy::primitives::LogData, block_number: u64, log_index: u64) -> Log {
+        Log {
+            inner: alloy::primitives::Log { address: crate::FOUR_MEME_CONTRACT_ADDRESS, data },
+            block_number: Some(block_number),
+            log_index: Some(log_index),
+            transaction_hash: Some(TxHash::repeat_byte(block_number as u8)),
+            ..Default::default()
+        }
+    }
+
+    fn create_log(block_number: u64, log_index: u64) -> Log {
+        let event = IFourMeme::TokenCreate {
+            creator: CREATOR,
+            token: TOKEN,
+            requestId: U256::from(7u64),
+            name: "Meme".into(),
+            symbol: "MEME".into(),
+            totalSupply: U256::from(1_000_000_000u64),
+            launchTime: U256::from(1_700_000_000u64),
+            launchFee: U256::ZERO,
+        };
+        log(event.encode_log_data(), block_number, log_index)
+    }
+
+    fn purchase_log(block_number: u64, log_index: u64) -> Log {
+        let event = IFourMeme::TokenPurchase {
+            token: TOKEN,
+            account: Address::repeat_byte(0xaa),
+            price: U256::from(1u64),
+            amount: U256::from(100u64),
+            cost: U256::from(10u64),
+            fee: U256::from(1u64),
+            offers: U256::ZERO,
+            funds: U256::from(10u64),
+        };
+        log(event.encode_log_data(), block_number, log_index)
+    }
+
+    fn event(log: &Log) -> FourMemeEvent {
+        FourMemeEvent::decode_log(log).unwrap()
+    }
+
+    #[test]
+    fn test_reingest_is_idempotent() {
+        let indexer = Indexer::in_memory().unwrap();
+        let events = [event(&create_log(1, 0)), event(&purchase_log(1, 1)), event(&purchase_log(2, 0))];
+
+        assert_eq!(indexer.ingest(&events).unwrap(), 3);
+        assert_eq!(indexer.ingest(&events).unwrap(), 0);
+
+        let tokens = indexer.tokens_created_by(CREATOR).unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!((tokens[0].symbol.as_str(), tokens[0].total_supply), ("MEME", U256::from(1_000_000_000u64)));
+        assert_eq!(indexer.token(TOKEN).unwrap(), Some(tokens[0].clone()));
+
+        let trades = indexer.trades_for_token(TOKEN).unwrap();
+        assert_eq!(trades.iter().map(|t| (t.fill.block_number, t.log_index)).collect::<Vec<_>>(), vec![(Some(1), 1), (Some(2), 0)]);
+        assert_eq!(trades[0].fill, TradeFill::from_event(&events[1]).unwrap());
+        assert_eq!(indexer.trades_by_account(Address::repeat_byte(0xaa)).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_removed_events_are_deleted() {
+        let indexer = Indexer::in_memory().unwrap();
+        indexer.ingest(&[event(&create_log(1, 0)), event(&purchase_log(2, 0))]).unwrap();
+
+        let mut removed = event(&purchase_log(2, 0));
+        removed.meta.removed = true;
+        assert_eq!(indexer.ingest(&[removed]).unwrap(), 1);
+
+        assert!(indexer.trades_for_token(TOKEN).unwrap().is_empty());
+        assert!(indexer.token(TOKEN).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_saves_checkpoint() {
+        let asserter = Asserter::new();
+        asserter.push_success(&vec![create_log(3, 0), purchase_log(4, 0)]);
+        let sdk = mocked_sdk(asserter).await;
+
+        let indexer = Indexer::in_memory().unwrap();
+        let checkpoint = indexer.backfill(&sdk, "main", 0, 9, ScanOptions::default()).await.unwrap();
+        assert_eq!(checkpoint, ScanCheckpoint::at_block(10));
+        assert_eq!(indexer.checkpoint("main").unwrap(), Some(checkpoint));
+        assert_eq!(indexer.trades_for_token(TOKEN).unwrap().len(), 1);
+
+        // Nothing left to fetch: the mock has no more responses.
+        assert_eq!(indexer.backfill(&sdk, "main", 0, 9, ScanOptions::default()).await.unwrap(), checkpoint);
+    }
+
+    #[test]
+    fn test_removed_event_moves_checkpoint_back() {
+        let indexer = Indexer::in_memory().unwrap();
+        for log in [create_log(3, 0), purchase_log(4, 0), purchase_log(4, 1)] {
+            indexer.ingest_followed(&event(&log), "live").unwrap();
+        }
+        let after = LogPosition { block_number: 4, log_index: 1 };
+        assert_eq!(indexer.checkpoint("live").unwrap(), Some(ScanCheckpoint { next_block: 4, last: Some(after) }));
+
+        // Block 4 was replaced: its events come back removed, latest first.
+        for log in [purchase_log(4, 1), purchase_log(4, 0)] {
+            let mut removed = event(&log);
+            removed.meta.removed = true;
+            indexer.ingest_followed(&removed, "live").unwrap();
+        }
+        assert_eq!(indexer.checkpoint("live").unwrap(), Some(ScanCheckpoint::at_block(4)));
+        assert!(indexer.trades_for_token(TOKEN).unwrap().is_empty());
+
+        // A removed event past the checkpoint leaves it alone.
+        let mut removed = event(&purchase_log(6, 0));
+        removed.meta.removed = true;
+        indexer.ingest_followed(&removed, "live").unwrap();
+        assert_eq!(indexer.checkpoint("live").unwrap(), Some(ScanCheckpoint::at_block(4)));
+    }
+
+    #[tokio::test]
+    async fn test_follow_returns_rpc_errors() {
+        let asserter = Asserter::new();
+        asserter.push_failure_msg("header not found");
+        let sdk = mocked_sdk(asserter).await;
+
+        let indexer = Indexer::in_memory().unwrap();
+        indexer.save_checkpoint("live", ScanCheckpoint::at_block(5)).unwrap();
+        let options = SubscriptionOptions::default().with_retry_delay(std::time::Duration::from_secs(3600));
+
+        assert!(indexer.follow(&sdk, "live", options).await.is_err());
+        assert_eq!(indexer.checkpoint("live").unwrap(), Some(ScanCheckpoint::at_block(5)));
+    }
+}
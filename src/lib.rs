@@ -3,9 +3,52 @@
 mod error;
 mod client;
 mod types;
-mod tx_context;
+mod gas;
+mod gas_limit;
+mod nonce;
+mod replace;
+mod subscription;
+mod filter;
+mod scan;
+mod stream;
+mod call;
+mod mempool;
+mod candle;
+mod portfolio;
+mod export;
+mod listing;
+#[cfg(feature = "indexer")]
+mod indexer;
+mod curve;
+mod revert;
+mod pending;
+mod fill;
+mod slippage;
+#[cfg(test)]
+mod test_utils;
 
 pub use error::*;
 pub use client::*;
 pub use types::*;
+pub use curve::*;
+pub use revert::*;
+pub use pending::*;
+pub use fill::*;
+pub use slippage::*;
+pub use gas::*;
+pub use gas_limit::{GasLimitEstimator, GasLimitPolicy};
+pub use nonce::*;
+pub use replace::*;
+pub use subscription::*;
+pub use filter::*;
+pub use scan::*;
+pub use stream::*;
+pub use call::*;
+pub use mempool::*;
+pub use candle::*;
+pub use portfolio::*;
+pub use export::*;
+pub use listing::*;
+#[cfg(feature = "indexer")]
+pub use indexer::*;
 
@@ -0,0 +1,159 @@
+use std::ops::{Bound, Range, RangeBounds};
+
+use alloy::primitives::{Address, U256};
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{FourMemeSdk, Result, TokenManager3::TokenInfo};
+
+
+const DEFAULT_CONCURRENCY: usize = 8;
+
+
+/// `_tokenInfoExs`: who launched a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenInfoEx {
+    pub creator: Address,
+    pub founder: Address,
+    pub reserves: U256,
+}
+
+/// A token from the TokenManager's `_tokens` list with its curve and launch details.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListedToken {
+    /// Position in `_tokens`, in launch order.
+    pub index: u64,
+    pub address: Address,
+    pub info: TokenInfo,
+    pub info_ex: TokenInfoEx,
+}
+
+
+impl FourMemeSdk {
+    /// Number of tokens launched through the TokenManager (`_tokenCount`).
+    pub async fn token_count(&self) -> Result<u64> {
+        let count = self.contract._tokenCount().call().await?;
+        Ok(u64::try_from(count).unwrap_or(u64::MAX))
+    }
+
+    /// Address of the `index`-th launched token (`_tokens`).
+    pub async fn token_at(&self, index: u64) -> Result<Address> {
+        Ok(self.contract._tokens(U256::from(index)).call().await?)
+    }
+
+    pub async fn token_info_ex(&self, token: Address) -> Result<TokenInfoEx> {
+        let res = self.contract._tokenInfoExs(token).call().await?;
+
+        Ok(TokenInfoEx {
+            creator: res.creator,
+            founder: res.founder,
+            reserves: res.reserves,
+        })
+    }
+
+    /// The `index`-th token joined with its `_tokenInfos` and `_tokenInfoExs`.
+    pub async fn listed_token(&self, index: u64) -> Result<ListedToken> {
+        let address = self.token_at(index).await?;
+        let (info, info_ex) = futures::try_join!(self.token_info(address), self.token_info_ex(address))?;
+
+        Ok(ListedToken { index, address, info, info_ex })
+    }
+
+    /// Tokens at the given `_tokens` indices, clamped to `_tokenCount`, in index order.
+    pub async fn list_tokens(&self, range: impl RangeBounds<u64>) -> Result<Vec<ListedToken>> {
+        self.list_tokens_with(range, DEFAULT_CONCURRENCY).await
+    }
+
+    /// Like [`list_tokens`](Self::list_tokens), fetching at most `concurrency` tokens at once.
+    pub async fn list_tokens_with(&self, range: impl RangeBounds<u64>, concurrency: usize) -> Result<Vec<ListedToken>> {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let count = self.token_count().await?;
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.saturating_add(1).min(count),
+            Bound::Excluded(end) => (*end).min(count),
+            Bound::Unbounded => count,
+        };
+
+        self.listed_tokens(start..end.max(start), concurrency).try_collect().await
+    }
+
+    /// Pages through every launched token in index order, with at most `concurrency` tokens
+    /// being fetched at once (three `eth_call`s each). A failed token is yielded as an `Err`
+    /// and the crawl goes on with the next one.
+    pub fn token_stream(&self, concurrency: usize) -> impl Stream<Item = Result<ListedToken>> + Send + 'static {
+        let sdk = self.clone();
+
+        stream::once(async move { sdk.token_count().await.map(|count| (sdk, count)) })
+            .flat_map(move |count| match count {
+                Ok((sdk, count)) => sdk.listed_tokens(0..count, concurrency).left_stream(),
+                Err(e) => stream::iter([Err(e)]).right_stream(),
+            })
+    }
+
+    fn listed_tokens(&self, range: Range<u64>, concurrency: usize) -> impl Stream<Item = Result<ListedToken>> + Send + 'static {
+        let sdk = self.clone();
+
+        stream::iter(range)
+            .map(move |index| {
+                let sdk = sdk.clone();
+                async move { sdk.listed_token(index).await }
+            })
+            .buffered(concurrency.max(1))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::Bytes,
+        sol_types::SolValue,
+        transports::mock::Asserter,
+    };
+
+    use super::*;
+    use crate::test_utils::mocked_sdk;
+
+    fn push_token(asserter: &Asserter, address: Address, offers: u64) {
+        let word = U256::from(offers);
+        let info = (address, Address::ZERO, word, word, word, word, word, word, word, word, word, word, U256::ZERO);
+        let info_ex = (Address::repeat_byte(0xcc), Address::repeat_byte(0xcc), U256::ZERO);
+
+        asserter.push_success(&Bytes::from(address.abi_encode()));
+        asserter.push_success(&Bytes::from(info.abi_encode_params()));
+        asserter.push_success(&Bytes::from(info_ex.abi_encode_params()));
+    }
+
+    #[tokio::test]
+    async fn test_list_tokens_clamps_to_count() {
+        let asserter = Asserter::new();
+        asserter.push_success(&Bytes::from(U256::from(3u64).abi_encode()));
+        push_token(&asserter, Address::repeat_byte(0x03), 7);
+
+        let tokens = mocked_sdk(asserter).await.list_tokens(2..=5).await.unwrap();
+
+        assert_eq!(tokens.len(), 1);
+        assert_eq!((tokens[0].index, tokens[0].address), (2, Address::repeat_byte(0x03)));
+        assert_eq!(tokens[0].info.offers, U256::from(7u64));
+        assert_eq!(tokens[0].info_ex.creator, Address::repeat_byte(0xcc));
+    }
+
+    #[tokio::test]
+    async fn test_token_stream_pages_in_order() {
+        let asserter = Asserter::new();
+        asserter.push_success(&Bytes::from(U256::from(2u64).abi_encode()));
+        push_token(&asserter, Address::repeat_byte(0x01), 1);
+        push_token(&asserter, Address::repeat_byte(0x02), 2);
+
+        let tokens: Vec<_> = mocked_sdk(asserter).await.token_stream(1).collect().await;
+
+        assert_eq!(tokens.iter().map(|t| t.as_ref().unwrap().address).collect::<Vec<_>>(), vec![
+            Address::repeat_byte(0x01),
+            Address::repeat_byte(0x02),
+        ]);
+    }
+}
@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use alloy::{
+    consensus::Transaction as _,
+    primitives::{Address, TxHash, B256, U256},
+    providers::Provider,
+    rpc::types::Transaction,
+    sol_types::SolInterface,
+};
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::{replace::sent_fees, FourMemeCall, FourMemeSdk, GasFees, Result, SubscriptionHandle};
+
+
+const PENDING_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Pending transactions looked up at once over HTTP.
+const FETCH_CONCURRENCY: usize = 16;
+const CHANNEL_CAPACITY: usize = 1024;
+
+
+/// A TokenManager trade seen in the mempool, before it was mined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingCall {
+    pub tx_hash: TxHash,
+    pub from: Address,
+    pub nonce: u64,
+    /// BNB sent with the call.
+    pub value: U256,
+    pub gas_fees: GasFees,
+    pub gas_limit: u64,
+    pub call: FourMemeCall,
+}
+
+impl PendingCall {
+    /// `None` unless `tx` is a still pending buy, sell or create call to `contract`.
+    pub fn from_transaction(tx: &Transaction, contract: Address) -> Option<Self> {
+        if tx.block_number.is_some() || tx.to() != Some(contract) {
+            return None;
+        }
+        let call = FourMemeCall::abi_decode(tx.input()).ok().filter(|call| call.trade_kind().is_some())?;
+
+        Some(Self {
+            tx_hash: *tx.inner.tx_hash(),
+            from: tx.inner.signer(),
+            nonce: tx.nonce(),
+            value: tx.value(),
+            gas_fees: sent_fees(tx),
+            gas_limit: tx.gas_limit(),
+            call,
+        })
+    }
+}
+
+
+impl FourMemeSdk {
+    /// Streams pending buy, sell and create calls to the TokenManager.
+    ///
+    /// Over WebSocket/IPC, full transactions are pushed with
+    /// `eth_subscribe("newPendingTransactions", true)` and filtered locally. Over HTTP, a
+    /// pending transaction filter is polled and each hash is looked up, skipping transactions
+    /// mined or dropped before the lookup. Either way the node must expose its mempool. The
+    /// stream ends when the node closes the subscription or the handle is cancelled.
+    pub async fn watch_pending_calls(
+        &self,
+    ) -> Result<(SubscriptionHandle, impl Stream<Item = Result<PendingCall>> + Send + 'static)> {
+        let contract = *self.contract.address();
+        let mut calls = match self.supports_pubsub() {
+            true => self.provider.subscribe_full_pending_transactions().await?
+                .into_stream()
+                .map(move |tx| Ok(PendingCall::from_transaction(&tx, contract)))
+                .boxed(),
+            false => {
+                let sdk = self.clone();
+                self.provider.watch_pending_transactions().await?
+                    .with_poll_interval(PENDING_POLL_INTERVAL)
+                    .into_stream()
+                    .flat_map(stream::iter)
+                    .map(move |hash| {
+                        let sdk = sdk.clone();
+                        async move { sdk.pending_call(hash).await }
+                    })
+                    .buffer_unordered(FETCH_CONCURRENCY)
+                    .boxed()
+            }
+        };
+
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let handle = SubscriptionHandle::spawn(async move {
+            while let Some(call) = calls.next().await {
+                let call = match call {
+                    Ok(Some(call)) => Ok(call),
+                    Ok(None) => continue,
+                    Err(e) => Err(e),
+                };
+                if tx.send(call).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        let calls = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|item| (item, rx)) });
+        Ok((handle, calls))
+    }
+
+    async fn pending_call(&self, tx_hash: B256) -> Result<Option<PendingCall>> {
+        let tx = self.provider.get_transaction_by_hash(tx_hash).await?;
+        Ok(tx.and_then(|tx| PendingCall::from_transaction(&tx, *self.contract.address())))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::{transaction::Recovered, SignableTransaction, TxEnvelope, TxLegacy},
+        primitives::{Signature, TxKind},
+        sol_types::SolCall,
+        transports::mock::Asserter,
+    };
+
+    use super::*;
+    use crate::test_utils::mocked_sdk;
+    use crate::{IFourMeme, FOUR_MEME_CONTRACT_ADDRESS};
+
+    fn pending_tx(to: Address, input: Vec<u8>, from: Address) -> Transaction {
+        let legacy = TxLegacy {
+            nonce: 9,
+            gas_price: 1_000_000_000,
+            gas_limit: 400_000,
+            to: TxKind::Call(to),
+            value: U256::from(10u64),
+            input: input.into(),
+            ..Default::default()
+        };
+        let signed = legacy.into_signed(Signature::new(U256::from(1u64), U256::from(1u64), false));
+
+        Transaction {
+            inner: Recovered::new_unchecked(TxEnvelope::Legacy(signed), from),
+            block_hash: None,
+            block_number: None,
+            transaction_index: None,
+            effective_gas_price: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pending_trades_are_decoded() {
+        let buy = IFourMeme::buyTokenAMAP_1Call {
+            token: Address::repeat_byte(0x44),
+            funds: U256::from(10u64),
+            minAmount: U256::from(1u64),
+        };
+        let trade = pending_tx(FOUR_MEME_CONTRACT_ADDRESS, buy.abi_encode(), Address::repeat_byte(0xaa));
+        let unrelated = pending_tx(Address::repeat_byte(0x01), buy.abi_encode(), Address::repeat_byte(0xbb));
+
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x1"); // eth_newPendingTransactionFilter
+        asserter.push_success(&vec![*unrelated.inner.tx_hash(), *trade.inner.tx_hash()]);
+        asserter.push_success(&unrelated);
+        asserter.push_success(&trade);
+
+        let sdk = mocked_sdk(asserter).await;
+
+        let (handle, calls) = sdk.watch_pending_calls().await.unwrap();
+        let mut calls = Box::pin(calls);
+        let call = calls.next().await.unwrap().unwrap();
+        handle.shutdown().await;
+
+        assert_eq!(call.tx_hash, *trade.inner.tx_hash());
+        assert_eq!(call.from, Address::repeat_byte(0xaa));
+        assert_eq!((call.nonce, call.value, call.gas_limit), (9, U256::from(10u64), 400_000));
+        assert_eq!(call.gas_fees, GasFees::Legacy { gas_price: 1_000_000_000 });
+        assert_eq!(call.call, FourMemeCall::buyTokenAMAP_1(buy));
+    }
+}
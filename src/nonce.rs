@@ -0,0 +1,176 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use alloy::{
+    primitives::Address,
+    providers::{DynProvider, Provider},
+};
+use tokio::sync::Mutex;
+
+use crate::Result;
+
+
+#[derive(Debug, Default)]
+struct SignerNonces {
+    next: u64,
+    /// Nonces handed out but never mined (send failed or tx dropped), reused before `next`.
+    released: BTreeSet<u64>,
+}
+
+/// Hands out sequential nonces per signer without asking the node for every transaction.
+///
+/// Clones share state, so concurrent trades from clones of one `FourMemeSdk` never
+/// reuse a nonce. The first nonce of a signer is read from the pending block.
+#[derive(Debug, Clone, Default)]
+pub struct NonceManager {
+    signers: Arc<Mutex<HashMap<Address, SignerNonces>>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserves the next nonce of `signer`, filling gaps left by released nonces first.
+    pub async fn next(&self, provider: &DynProvider, signer: Address) -> Result<u64> {
+        let mut signers = self.signers.lock().await;
+
+        let nonces = match signers.get_mut(&signer) {
+            Some(nonces) => nonces,
+            None => {
+                let next = provider.get_transaction_count(signer).pending().await?;
+                signers.entry(signer).or_insert(SignerNonces { next, released: BTreeSet::new() })
+            }
+        };
+
+        if let Some(nonce) = nonces.released.pop_first() {
+            return Ok(nonce);
+        }
+
+        let nonce = nonces.next;
+        nonces.next += 1;
+        Ok(nonce)
+    }
+
+    /// Returns a reserved nonce that will never be mined, so the next trade reuses it
+    /// instead of leaving a gap that blocks every later transaction.
+    pub async fn release(&self, signer: Address, nonce: u64) {
+        let mut signers = self.signers.lock().await;
+
+        if let Some(nonces) = signers.get_mut(&signer) {
+            if nonce + 1 == nonces.next {
+                nonces.next = nonce;
+            } else if nonce < nonces.next {
+                nonces.released.insert(nonce);
+            }
+        }
+    }
+
+    /// Re-reads the pending nonce of `signer` from the node, e.g. after "nonce too low".
+    pub async fn resync(&self, provider: &DynProvider, signer: Address) -> Result<u64> {
+        let next = provider.get_transaction_count(signer).pending().await?;
+
+        let mut signers = self.signers.lock().await;
+        let nonces = signers.entry(signer).or_default();
+        nonces.next = next;
+        nonces.released.clear();
+
+        Ok(next)
+    }
+
+    /// Forgets `signer`; its next nonce is read from the node again.
+    pub async fn reset(&self, signer: Address) {
+        self.signers.lock().await.remove(&signer);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+    use alloy::transports::mock::Asserter;
+
+    use super::*;
+    use crate::test_utils::mocked_provider;
+    use crate::{BuyAmapParams, FixedGas, FourMemeSdk, GasFees};
+
+    #[tokio::test]
+    async fn test_sequential_nonces_shared_across_clones() {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x7");
+        let provider = mocked_provider(asserter);
+        let signer = Address::repeat_byte(1);
+
+        let manager = NonceManager::new();
+        let clone = manager.clone();
+        let (a, b) = tokio::join!(manager.next(&provider, signer), clone.next(&provider, signer));
+        let mut got = vec![a.unwrap(), b.unwrap()];
+        got.sort();
+
+        assert_eq!(got, vec![7, 8]);
+        assert_eq!(manager.next(&provider, signer).await.unwrap(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_released_nonces_fill_gaps() {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x0");
+        let provider = mocked_provider(asserter);
+        let signer = Address::repeat_byte(2);
+
+        let manager = NonceManager::new();
+        for expected in 0..4 {
+            assert_eq!(manager.next(&provider, signer).await.unwrap(), expected);
+        }
+
+        manager.release(signer, 1).await;
+        manager.release(signer, 3).await;
+        assert_eq!(manager.next(&provider, signer).await.unwrap(), 1);
+        assert_eq!(manager.next(&provider, signer).await.unwrap(), 3);
+        assert_eq!(manager.next(&provider, signer).await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_resync_after_nonce_too_low() {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x1");
+        asserter.push_success(&"0x5");
+        let provider = mocked_provider(asserter);
+        let signer = Address::repeat_byte(3);
+
+        let manager = NonceManager::new();
+        assert_eq!(manager.next(&provider, signer).await.unwrap(), 1);
+        manager.release(signer, 0).await;
+
+        assert_eq!(manager.resync(&provider, signer).await.unwrap(), 5);
+        assert_eq!(manager.next(&provider, signer).await.unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_trade_retries_once_on_nonce_too_low() {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x186a0"); // eth_estimateGas
+        asserter.push_success(&"0x3"); // eth_getTransactionCount
+        asserter.push_success(&"0x38"); // eth_chainId, filled once by the provider
+        asserter.push_failure_msg("nonce too low");
+        asserter.push_success(&"0x5"); // resync
+        asserter.push_success(&alloy::primitives::TxHash::repeat_byte(9));
+
+        let signer = Address::repeat_byte(4);
+        let sdk = FourMemeSdk::new_with_provider(mocked_provider(asserter), None, None).await.unwrap()
+            .with_gas_strategy(FixedGas(GasFees::Legacy { gas_price: 1 }))
+            .with_signer(signer);
+
+        let pending = sdk.buy_token_amap(BuyAmapParams {
+            token: Address::repeat_byte(5),
+            funds: U256::from(1u64),
+            min_amount: U256::ZERO,
+            to: None,
+        }).await.unwrap();
+
+        assert_eq!(pending.nonce(), Some((signer, 5)));
+        assert_eq!(sdk.nonces.next(&sdk.provider, signer).await.unwrap(), 6);
+    }
+}
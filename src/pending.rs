@@ -0,0 +1,327 @@
+use std::time::Duration;
+
+use alloy::{
+    primitives::{Address, Bytes, Selector, TxHash},
+    providers::Provider,
+    rpc::types::TransactionReceipt,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{ContractRevert, FourMemeError, FourMemeEvent, FourMemeSdk, Result, TokenManagerEvent};
+
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Consecutive polls where the node no longer knows the transaction before it is reported dropped.
+const DEFAULT_DROPPED_AFTER: u32 = 5;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TradeKind {
+    Buy,
+    Sell,
+    Create,
+}
+
+/// A mined trade with the TokenManager event it emitted.
+#[derive(Clone)]
+pub struct ConfirmedTrade {
+    pub receipt: TransactionReceipt,
+    /// `TokenPurchase`, `TokenSale` or `TokenCreate` depending on the trade kind.
+    pub event: Option<FourMemeEvent>,
+    pub confirmations: u64,
+}
+
+/// Handle to a submitted TokenManager transaction.
+#[derive(Clone)]
+pub struct PendingTrade {
+    pub(crate) sdk: FourMemeSdk,
+    tx_hash: TxHash,
+    kind: TradeKind,
+    selector: Option<Selector>,
+    /// Signer and nonce reserved from the SDK's `NonceManager`.
+    nonce: Option<(Address, u64)>,
+    poll_interval: Duration,
+    dropped_after: u32,
+}
+
+impl std::fmt::Debug for PendingTrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingTrade")
+            .field("tx_hash", &self.tx_hash)
+            .field("kind", &self.kind)
+            .finish()
+    }
+}
+
+impl PendingTrade {
+    pub(crate) fn new(sdk: FourMemeSdk, tx_hash: TxHash, kind: TradeKind) -> Self {
+        Self {
+            sdk,
+            tx_hash,
+            kind,
+            selector: None,
+            nonce: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            dropped_after: DEFAULT_DROPPED_AFTER,
+        }
+    }
+
+    /// Function called by the transaction, so its gas usage can be learned once mined.
+    pub(crate) fn with_selector(mut self, selector: Option<Selector>) -> Self {
+        self.selector = selector;
+        self
+    }
+
+    pub(crate) fn with_nonce(mut self, signer: Address, nonce: u64) -> Self {
+        self.nonce = Some((signer, nonce));
+        self
+    }
+
+    /// Signer and nonce the transaction was sent with, when the SDK assigned them.
+    pub fn nonce(&self) -> Option<(Address, u64)> {
+        self.nonce
+    }
+
+    /// Same trade, now tracked through the transaction that replaced it.
+    pub(crate) fn replaced_by(&self, tx_hash: TxHash) -> Self {
+        Self { tx_hash, ..self.clone() }
+    }
+
+    pub fn tx_hash(&self) -> TxHash {
+        self.tx_hash
+    }
+
+    pub fn kind(&self) -> TradeKind {
+        self.kind
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Number of consecutive polls with the transaction missing from the node before giving up.
+    pub fn with_dropped_after(mut self, polls: u32) -> Self {
+        self.dropped_after = polls.max(1);
+        self
+    }
+
+    /// Waits until the transaction has `confirmations` blocks on top of it (1 = mined).
+    ///
+    /// Fails with `Timeout` if that takes longer than `timeout`, with `Dropped` if the node
+    /// forgets the transaction, and with `Revert` if it was mined but failed.
+    pub async fn wait(&self, confirmations: u64, timeout: Duration) -> Result<ConfirmedTrade> {
+        tokio::time::timeout(timeout, self.wait_inner(confirmations.max(1)))
+            .await
+            .map_err(|_| FourMemeError::Timeout(self.tx_hash))?
+    }
+
+    async fn wait_inner(&self, confirmations: u64) -> Result<ConfirmedTrade> {
+        let provider = &self.sdk.provider;
+        let mut missing = 0;
+        let mut sender: Option<(Address, u64)> = None;
+
+        loop {
+            if let Some(receipt) = provider.get_transaction_receipt(self.tx_hash).await? {
+                missing = 0;
+                let mined_at = receipt.block_number.unwrap_or_default();
+                let head = provider.get_block_number().await?;
+                let depth = head.saturating_sub(mined_at) + 1;
+
+                if depth >= confirmations {
+                    return self.finish(receipt, depth).await;
+                }
+            } else {
+                match provider.get_transaction_by_hash(self.tx_hash).await? {
+                    Some(tx) => {
+                        missing = 0;
+                        sender = Some((tx.inner.signer(), alloy::consensus::Transaction::nonce(&tx)));
+                    }
+                    None => {
+                        missing += 1;
+                        if missing >= self.dropped_after {
+                            // The nonce was never used: hand it back so the next trade fills the gap.
+                            if let Some((signer, nonce)) = self.nonce {
+                                if provider.get_transaction_count(signer).await? <= nonce {
+                                    self.sdk.nonces.release(signer, nonce).await;
+                                }
+                            }
+                            return Err(FourMemeError::Dropped(self.tx_hash));
+                        }
+                    }
+                }
+
+                // Another transaction took the nonce: this one can never be mined.
+                if let Some((from, nonce)) = sender {
+                    if provider.get_transaction_count(from).await? > nonce
+                        && provider.get_transaction_receipt(self.tx_hash).await?.is_none()
+                    {
+                        return Err(FourMemeError::Dropped(self.tx_hash));
+                    }
+                }
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    async fn finish(&self, receipt: TransactionReceipt, confirmations: u64) -> Result<ConfirmedTrade> {
+        if !receipt.status() {
+            // The transaction failed on-chain either way; a failed replay only loses the reason.
+            let (reason, data) = self.sdk.replay_failed_tx(&receipt).await
+                .unwrap_or((ContractRevert::Empty, Bytes::new()));
+            return Err(FourMemeError::Revert { reason, data });
+        }
+
+        if let Some(selector) = self.selector {
+            self.sdk.gas_limits.record(selector, receipt.gas_used);
+        }
+
+        let contract = *self.sdk.contract.address();
+        let event = receipt.logs().iter()
+            .filter(|log| log.address() == contract)
+            .filter_map(FourMemeEvent::decode_log)
+            .find(|event| matches!(
+                (self.kind, &event.event),
+                (TradeKind::Buy, TokenManagerEvent::TokenPurchase(_))
+                    | (TradeKind::Sell, TokenManagerEvent::TokenSale(_))
+                    | (TradeKind::Create, TokenManagerEvent::TokenCreate(_))
+            ));
+
+        Ok(ConfirmedTrade { receipt, event, confirmations })
+    }
+}
+
+impl FourMemeSdk {
+    /// Re-attaches to a trade submitted earlier, e.g. after a restart.
+    pub fn pending_trade(&self, tx_hash: TxHash, kind: TradeKind) -> PendingTrade {
+        PendingTrade::new(self.clone(), tx_hash, kind)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::consensus::{Signed, TxEnvelope, TxLegacy};
+    use alloy::primitives::{Signature, U256};
+    use alloy::rpc::json_rpc::ErrorPayload;
+    use alloy::rpc::types::Transaction;
+    use alloy::sol_types::{Revert, SolError};
+    use alloy::transports::mock::Asserter;
+
+    use super::*;
+    use crate::test_utils::mocked_sdk;
+
+    fn failed_receipt(tx_hash: TxHash) -> serde_json::Value {
+        serde_json::json!({
+            "type": "0x0",
+            "status": "0x0",
+            "transactionHash": tx_hash,
+            "transactionIndex": "0x0",
+            "blockHash": TxHash::repeat_byte(0xbb),
+            "blockNumber": "0x64",
+            "from": Address::repeat_byte(0xaa),
+            "to": crate::FOUR_MEME_CONTRACT_ADDRESS,
+            "contractAddress": null,
+            "gasUsed": "0x5208",
+            "cumulativeGasUsed": "0x5208",
+            "effectiveGasPrice": "0x3b9aca00",
+            "logs": [],
+            "logsBloom": alloy::primitives::Bloom::default(),
+        })
+    }
+
+    fn mined_tx() -> Transaction {
+        let tx = TxLegacy { gas_limit: 400_000, to: crate::FOUR_MEME_CONTRACT_ADDRESS.into(), ..Default::default() };
+        let signed = Signed::new_unhashed(tx, Signature::new(U256::from(1u64), U256::from(1u64), false));
+
+        Transaction {
+            inner: alloy::consensus::transaction::Recovered::new_unchecked(TxEnvelope::Legacy(signed), Address::repeat_byte(0xaa)),
+            block_hash: Some(TxHash::repeat_byte(0xbb)),
+            block_number: Some(100),
+            transaction_index: Some(0),
+            effective_gas_price: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_reports_dropped_transaction() {
+        let asserter = Asserter::new();
+        for _ in 0..2 {
+            asserter.push_success(&serde_json::Value::Null); // eth_getTransactionReceipt
+            asserter.push_success(&serde_json::Value::Null); // eth_getTransactionByHash
+        }
+
+        let sdk = mocked_sdk(asserter).await;
+        let pending = sdk.pending_trade(TxHash::repeat_byte(1), TradeKind::Buy)
+            .with_poll_interval(Duration::from_millis(1))
+            .with_dropped_after(2);
+
+        let err = pending.wait(1, Duration::from_secs(5)).await.err().expect("wait should fail");
+        assert!(matches!(err, FourMemeError::Dropped(hash) if hash == TxHash::repeat_byte(1)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_times_out() {
+        // Time is paused: polls run at 0, 20 and 40ms, then the 50ms timeout fires.
+        let asserter = Asserter::new();
+        for _ in 0..3 {
+            asserter.push_success(&serde_json::Value::Null);
+            asserter.push_success(&serde_json::Value::Null);
+        }
+
+        let sdk = mocked_sdk(asserter).await;
+        let pending = sdk.pending_trade(TxHash::repeat_byte(2), TradeKind::Sell)
+            .with_poll_interval(Duration::from_millis(20))
+            .with_dropped_after(u32::MAX);
+
+        let err = pending.wait(1, Duration::from_millis(50)).await.err().expect("wait should fail");
+        assert!(matches!(err, FourMemeError::Timeout(_)));
+        // The trade may still be mined: wait again, never re-send.
+        assert!(!err.is_retryable());
+        assert!(err.can_wait_again());
+    }
+
+    #[tokio::test]
+    async fn test_failed_trade_keeps_revert_data() {
+        let tx_hash = TxHash::repeat_byte(3);
+        let data = Revert::from("Slippage").abi_encode();
+
+        let asserter = Asserter::new();
+        asserter.push_success(&failed_receipt(tx_hash));
+        asserter.push_success(&100u64); // eth_blockNumber
+        asserter.push_success(&mined_tx()); // eth_getTransactionByHash
+        asserter.push_failure(ErrorPayload {
+            code: 3,
+            message: "execution reverted: Slippage".into(),
+            data: Some(serde_json::value::to_raw_value(&format!("0x{}", alloy::hex::encode(&data))).unwrap()),
+        });
+
+        let err = mocked_sdk(asserter).await.pending_trade(tx_hash, TradeKind::Buy)
+            .wait(1, Duration::from_secs(5)).await.err().expect("wait should fail");
+
+        match err {
+            FourMemeError::Revert { reason, data: raw } => {
+                assert_eq!(reason, ContractRevert::Slippage("Slippage".to_string()));
+                assert_eq!(raw.as_ref(), data.as_slice());
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_replay_still_reports_revert() {
+        let tx_hash = TxHash::repeat_byte(4);
+
+        let asserter = Asserter::new();
+        asserter.push_success(&failed_receipt(tx_hash));
+        asserter.push_success(&100u64);
+        asserter.push_failure_msg("connection reset"); // eth_getTransactionByHash
+
+        let err = mocked_sdk(asserter).await.pending_trade(tx_hash, TradeKind::Sell)
+            .wait(1, Duration::from_secs(5)).await.err().expect("wait should fail");
+
+        assert!(matches!(err, FourMemeError::Revert { reason: ContractRevert::Empty, .. }), "{err:?}");
+    }
+}
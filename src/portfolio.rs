@@ -0,0 +1,391 @@
+use std::collections::BTreeMap;
+
+use alloy::{
+    primitives::{Address, I256, U256},
+    sol,
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    curve::PRICE_SCALE, BondingCurve, FourMemeError, FourMemeEvent, FourMemeSdk, Result, ScanOptions,
+    SubscriptionFilter, TradeFill, TradeSide,
+};
+
+
+sol! {
+    #[sol(rpc)]
+    interface IPancakeFactory {
+        function getPair(address tokenA, address tokenB) external view returns (address pair);
+    }
+
+    #[sol(rpc)]
+    interface IPancakePair {
+        function token0() external view returns (address);
+        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast);
+    }
+}
+
+
+fn signed(value: U256) -> I256 {
+    I256::try_from(value).unwrap_or(I256::MAX)
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkSource {
+    /// Last price of the bonding curve (`_tokenInfos`).
+    Curve,
+    /// Reserves of the PancakeSwap pair the token graduated to.
+    PancakeSwap,
+}
+
+/// Market price of a token, quote wei per whole token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mark {
+    pub price: U256,
+    pub source: MarkSource,
+}
+
+impl Mark {
+    /// Spot price of a pool holding `token_reserve` tokens against `quote_reserve`.
+    pub fn from_reserves(token_reserve: U256, quote_reserve: U256) -> Option<Self> {
+        let price = quote_reserve.checked_mul(PRICE_SCALE)?.checked_div(token_reserve)?;
+        Some(Self { price, source: MarkSource::PancakeSwap })
+    }
+
+    /// Value of `amount` tokens at this price.
+    pub fn value(&self, amount: U256) -> U256 {
+        amount.saturating_mul(self.price) / PRICE_SCALE
+    }
+}
+
+
+/// A wallet's holding of one token, reconstructed from its trades with average cost.
+///
+/// Amounts are in the token's quote (BNB unless the token launched against another quote).
+/// Tokens sold beyond what the wallet bought (received by transfer) carry no cost basis.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub token: Address,
+    /// Tokens held according to the trades.
+    pub amount: U256,
+    /// Quote paid for `amount`, fees included.
+    pub cost_basis: U256,
+    pub bought: U256,
+    pub sold: U256,
+    /// Quote spent on buys, fees included.
+    pub invested: U256,
+    /// Quote received from sells, after fees.
+    pub proceeds: U256,
+    pub fees: U256,
+    pub realized_pnl: I256,
+    pub trades: u64,
+    /// Set by [`FourMemeSdk::mark_to_market`].
+    pub mark: Option<Mark>,
+}
+
+impl Position {
+    pub fn new(token: Address) -> Self {
+        Self {
+            token,
+            amount: U256::ZERO,
+            cost_basis: U256::ZERO,
+            bought: U256::ZERO,
+            sold: U256::ZERO,
+            invested: U256::ZERO,
+            proceeds: U256::ZERO,
+            fees: U256::ZERO,
+            realized_pnl: I256::ZERO,
+            trades: 0,
+            mark: None,
+        }
+    }
+
+    pub fn apply(&mut self, fill: &TradeFill) {
+        self.trades += 1;
+        self.fees = self.fees.saturating_add(fill.fee);
+        let net = fill.net_funds();
+
+        match fill.side {
+            TradeSide::Buy => {
+                self.amount = self.amount.saturating_add(fill.amount);
+                self.cost_basis = self.cost_basis.saturating_add(net);
+                self.bought = self.bought.saturating_add(fill.amount);
+                self.invested = self.invested.saturating_add(net);
+            }
+            TradeSide::Sell => {
+                let covered = fill.amount.min(self.amount);
+                let basis = match self.amount.is_zero() {
+                    true => U256::ZERO,
+                    false => self.cost_basis.saturating_mul(covered) / self.amount,
+                };
+
+                self.amount -= covered;
+                self.cost_basis -= basis;
+                self.sold = self.sold.saturating_add(fill.amount);
+                self.proceeds = self.proceeds.saturating_add(net);
+                self.realized_pnl = self.realized_pnl.saturating_add(signed(net).saturating_sub(signed(basis)));
+            }
+        }
+    }
+
+    /// Average cost of the tokens held, quote wei per whole token.
+    pub fn average_cost(&self) -> Option<U256> {
+        self.cost_basis.checked_mul(PRICE_SCALE)?.checked_div(self.amount)
+    }
+
+    pub fn market_value(&self) -> Option<U256> {
+        Some(self.mark?.value(self.amount))
+    }
+
+    pub fn unrealized_pnl(&self) -> Option<I256> {
+        Some(signed(self.market_value()?).saturating_sub(signed(self.cost_basis)))
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.amount.is_zero()
+    }
+}
+
+
+/// Positions of one wallet, keyed by token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub wallet: Address,
+    pub positions: BTreeMap<Address, Position>,
+}
+
+impl Portfolio {
+    pub fn new(wallet: Address) -> Self {
+        Self { wallet, positions: BTreeMap::new() }
+    }
+
+    /// Replays `events` in chain order; trades of other accounts and events removed by a
+    /// reorg are skipped.
+    pub fn from_events<'a>(wallet: Address, events: impl IntoIterator<Item = &'a FourMemeEvent>) -> Self {
+        let mut portfolio = Self::new(wallet);
+        events.into_iter().for_each(|event| portfolio.apply(event));
+        portfolio
+    }
+
+    pub fn apply(&mut self, event: &FourMemeEvent) {
+        if event.meta.removed {
+            return;
+        }
+        let Some(fill) = TradeFill::from_event(event).filter(|fill| fill.account == self.wallet) else {
+            return;
+        };
+
+        self.positions.entry(fill.token).or_insert_with(|| Position::new(fill.token)).apply(&fill);
+    }
+
+    pub fn position(&self, token: Address) -> Option<&Position> {
+        self.positions.get(&token)
+    }
+
+    pub fn open_positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values().filter(|position| position.is_open())
+    }
+
+    pub fn fees(&self) -> U256 {
+        self.positions.values().fold(U256::ZERO, |sum, position| sum.saturating_add(position.fees))
+    }
+
+    pub fn realized_pnl(&self) -> I256 {
+        self.positions.values().fold(I256::ZERO, |sum, position| sum.saturating_add(position.realized_pnl))
+    }
+
+    /// Unrealized PnL of the marked open positions.
+    pub fn unrealized_pnl(&self) -> I256 {
+        self.open_positions()
+            .filter_map(Position::unrealized_pnl)
+            .fold(I256::ZERO, |sum, pnl| sum.saturating_add(pnl))
+    }
+}
+
+
+/// TokenManager constants that marks depend on, read once per [`FourMemeSdk::mark_to_market`].
+struct Market {
+    status_completed: U256,
+    wbnb: Address,
+    factory: Address,
+}
+
+
+impl FourMemeSdk {
+    /// Reconstructs `wallet`'s positions from its trades in `from_block..=to_block` and marks
+    /// the open ones to market.
+    pub async fn portfolio(&self, wallet: Address, from_block: u64, to_block: u64, options: ScanOptions) -> Result<Portfolio> {
+        let filter = SubscriptionFilter::new().trades().trader(wallet);
+        let mut events = Box::pin(self.scan_events_with(from_block, to_block, filter, options));
+        let mut portfolio = Portfolio::new(wallet);
+
+        while let Some(event) = events.next().await {
+            portfolio.apply(&event?);
+        }
+
+        self.mark_to_market(&mut portfolio).await?;
+        Ok(portfolio)
+    }
+
+    /// Sets the [`Mark`] of every open position.
+    pub async fn mark_to_market(&self, portfolio: &mut Portfolio) -> Result<()> {
+        let market = self.market().await?;
+        for position in portfolio.positions.values_mut().filter(|position| position.is_open()) {
+            position.mark = Some(self.mark_price_in(&market, position.token).await?);
+        }
+        Ok(())
+    }
+
+    /// Current price of `token`: from its PancakeSwap V2 pair once the token is in
+    /// `STATUS_COMPLETED` and the pair holds liquidity, otherwise the last price of the bonding curve.
+    pub async fn mark_price(&self, token: Address) -> Result<Mark> {
+        self.mark_price_in(&self.market().await?, token).await
+    }
+
+    async fn market(&self) -> Result<Market> {
+        Ok(Market {
+            status_completed: self.contract.STATUS_COMPLETED().call().await?,
+            wbnb: self.contract.WBNB().call().await?,
+            factory: self.contract.PANCAKE_FACTORY().call().await?,
+        })
+    }
+
+    async fn mark_price_in(&self, market: &Market, token: Address) -> Result<Mark> {
+        let ti = self.token_info(token).await?;
+
+        if ti.status == market.status_completed {
+            let quote = match ti.quote.is_zero() {
+                true => market.wbnb,
+                false => ti.quote,
+            };
+            if let Some(mark) = self.pool_mark(market.factory, token, quote).await? {
+                return Ok(mark);
+            }
+        }
+
+        let price = BondingCurve::from(&ti).calc_last_price()
+            .ok_or_else(|| FourMemeError::Validation(format!("Invalid curve state for token {}", token)))?;
+        Ok(Mark { price, source: MarkSource::Curve })
+    }
+
+    async fn pool_mark(&self, factory: Address, token: Address, quote: Address) -> Result<Option<Mark>> {
+        let pair = IPancakeFactory::new(factory, &self.provider).getPair(token, quote).call().await?;
+        if pair.is_zero() {
+            return Ok(None);
+        }
+
+        let pair = IPancakePair::new(pair, &self.provider);
+        let reserves = pair.getReserves().call().await?;
+        let (reserve0, reserve1) = (U256::from(reserves.reserve0), U256::from(reserves.reserve1));
+        let (token_reserve, quote_reserve) = match pair.token0().call().await? == token {
+            true => (reserve0, reserve1),
+            false => (reserve1, reserve0),
+        };
+
+        Ok(match token_reserve.is_zero() || quote_reserve.is_zero() {
+            true => None,
+            false => Mark::from_reserves(token_reserve, quote_reserve),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Bytes, TxHash},
+        sol_types::SolValue,
+        transports::mock::Asserter,
+    };
+
+    use super::*;
+    use crate::test_utils::mocked_sdk;
+
+    const TOKEN: Address = Address::repeat_byte(0x44);
+    const WALLET: Address = Address::repeat_byte(0xaa);
+
+    fn fill(side: TradeSide, amount: u64, cost: u64, fee: u64) -> TradeFill {
+        TradeFill {
+            tx_hash: TxHash::ZERO,
+            block_number: None,
+            side,
+            token: TOKEN,
+            account: WALLET,
+            amount: U256::from(amount),
+            cost: U256::from(cost),
+            fee: U256::from(fee),
+            price: U256::ZERO,
+            offers: U256::ZERO,
+            funds: U256::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_average_cost_and_realized_pnl() {
+        let mut position = Position::new(TOKEN);
+        position.apply(&fill(TradeSide::Buy, 100, 990, 10));
+        position.apply(&fill(TradeSide::Buy, 100, 2_970, 30));
+        assert_eq!(position.cost_basis, U256::from(4_000u64));
+        assert_eq!(position.average_cost(), Some(U256::from(20u64) * PRICE_SCALE));
+
+        // Half the position for 2_500 minus a 25 fee against a 2_000 basis.
+        position.apply(&fill(TradeSide::Sell, 100, 2_500, 25));
+        assert_eq!((position.amount, position.cost_basis), (U256::from(100u64), U256::from(2_000u64)));
+        assert_eq!(position.realized_pnl, I256::try_from(475).unwrap());
+        assert_eq!(position.fees, U256::from(65u64));
+
+        // Tokens received by transfer have no basis: selling them is all profit.
+        position.apply(&fill(TradeSide::Sell, 150, 1_000, 0));
+        assert!(!position.is_open());
+        assert_eq!(position.realized_pnl, I256::try_from(475 - 2_000 + 1_000).unwrap());
+    }
+
+    #[test]
+    fn test_unrealized_pnl_from_mark() {
+        let mut position = Position::new(TOKEN);
+        position.apply(&fill(TradeSide::Buy, 2_000_000, 1_000, 0));
+        assert_eq!(position.unrealized_pnl(), None);
+
+        // The pool prices a whole token at 0.0015 quote.
+        position.mark = Mark::from_reserves(U256::from(2_000_000u64) * PRICE_SCALE, U256::from(3_000u64) * PRICE_SCALE);
+        assert_eq!(position.mark.unwrap().source, MarkSource::PancakeSwap);
+        assert_eq!(position.market_value(), Some(U256::from(3_000u64)));
+        assert_eq!(position.unrealized_pnl(), Some(I256::try_from(2_000).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_only_graduated_tokens_use_the_pool() {
+        let (trading, graduated, pair) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22), Address::repeat_byte(0x33));
+        let token_info = |token: Address, status: u64| {
+            // K = 8, T = 2 and nothing sold: the curve's last price is 8e18 / 2 / 2.
+            let (k, t, zero) = (U256::from(8u64), U256::from(2u64), U256::ZERO);
+            (token, Address::ZERO, zero, zero, zero, zero, zero, zero, zero, zero, k, t, U256::from(status))
+                .abi_encode_params()
+        };
+
+        let asserter = Asserter::new();
+        for word in [U256::from(2u64).abi_encode(), Address::repeat_byte(0xbb).abi_encode(), Address::repeat_byte(0xfa).abi_encode()] {
+            asserter.push_success(&Bytes::from(word)); // STATUS_COMPLETED, WBNB, PANCAKE_FACTORY
+        }
+        asserter.push_success(&Bytes::from(token_info(trading, 0)));
+        asserter.push_success(&Bytes::from(token_info(graduated, 2)));
+        asserter.push_success(&Bytes::from(pair.abi_encode()));
+        asserter.push_success(&Bytes::from((U256::from(4_000u64), U256::from(6u64), U256::ZERO).abi_encode_params()));
+        asserter.push_success(&Bytes::from(graduated.abi_encode()));
+        let sdk = mocked_sdk(asserter).await;
+
+        let mut portfolio = Portfolio::new(WALLET);
+        for token in [trading, graduated, TOKEN] {
+            let mut position = Position::new(token);
+            position.amount = U256::from((token != TOKEN) as u64);
+            portfolio.positions.insert(token, position);
+        }
+        sdk.mark_to_market(&mut portfolio).await.unwrap();
+
+        assert_eq!(portfolio.position(trading).unwrap().mark, Some(Mark { price: U256::from(2u64) * PRICE_SCALE, source: MarkSource::Curve }));
+        assert_eq!(portfolio.position(graduated).unwrap().mark, Mark::from_reserves(U256::from(4_000u64), U256::from(6u64)));
+        assert_eq!(portfolio.position(TOKEN).unwrap().mark, None);
+    }
+}
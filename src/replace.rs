@@ -0,0 +1,283 @@
+use alloy::{
+    consensus::Transaction as _,
+    primitives::{Address, TxHash, U256},
+    providers::Provider,
+    rpc::types::{Transaction, TransactionRequest},
+};
+
+use crate::{FourMemeError, FourMemeSdk, GasFees, PendingTrade, Result};
+
+
+/// Most nodes refuse a replacement that does not raise every fee field by at least 10%.
+pub const MIN_REPLACEMENT_BUMP_PCT: u32 = 10;
+
+/// Fee increase used by [`FourMemeSdk::cancel`].
+pub const DEFAULT_CANCEL_BUMP_PCT: u32 = 20;
+
+const TRANSFER_GAS_LIMIT: u64 = 21_000;
+
+
+/// Fees a replacement must pay: the original fees raised by `bump_pct`, and never less than
+/// what the gas strategy asks for now. The original pricing path (legacy or EIP-1559) is kept.
+pub fn replacement_fees(sent: GasFees, bump_pct: u32, current: GasFees) -> GasFees {
+    match (sent.scale(100 + bump_pct), current) {
+        (GasFees::Legacy { gas_price }, current) => GasFees::Legacy {
+            gas_price: gas_price.max(current.max_gas_price()),
+        },
+        (
+            GasFees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas },
+            GasFees::Eip1559 { max_fee_per_gas: current_max_fee, max_priority_fee_per_gas: current_priority },
+        ) => GasFees::Eip1559 {
+            max_fee_per_gas: max_fee_per_gas.max(current_max_fee),
+            max_priority_fee_per_gas: max_priority_fee_per_gas.max(current_priority),
+        },
+        (GasFees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas }, GasFees::Legacy { gas_price }) => GasFees::Eip1559 {
+            max_fee_per_gas: max_fee_per_gas.max(gas_price),
+            max_priority_fee_per_gas,
+        },
+    }
+}
+
+pub(crate) fn sent_fees(tx: &Transaction) -> GasFees {
+    match (tx.gas_price(), tx.max_priority_fee_per_gas()) {
+        (Some(gas_price), _) => GasFees::Legacy { gas_price },
+        (None, priority) => GasFees::Eip1559 {
+            max_fee_per_gas: tx.max_fee_per_gas(),
+            max_priority_fee_per_gas: priority.unwrap_or_default(),
+        },
+    }
+}
+
+/// Clears the fee fields so that exactly one pricing path is set afterwards.
+fn without_fees(mut tx: TransactionRequest) -> TransactionRequest {
+    tx.gas_price = None;
+    tx.max_fee_per_gas = None;
+    tx.max_priority_fee_per_gas = None;
+    tx
+}
+
+
+impl FourMemeSdk {
+    /// Re-sends a pending transaction with the same calldata and nonce, raising its fees by `bump_pct`.
+    ///
+    /// Returns the hash of the replacement; the original will be reported `Dropped` once it is mined.
+    pub async fn speed_up(&self, tx_hash: TxHash, bump_pct: u32) -> Result<TxHash> {
+        let tx = self.pending_tx(tx_hash, bump_pct).await?;
+        let fees = replacement_fees(sent_fees(&tx), bump_pct, self.gas_fees().await?);
+        let from = tx.inner.signer();
+
+        let request = fees.apply(without_fees(tx.into_request()).from(from));
+        let pending = self.provider.send_transaction(request).await?;
+
+        Ok(*pending.tx_hash())
+    }
+
+    /// Replaces a pending transaction with a zero-value transfer to its own sender at the same nonce.
+    pub async fn cancel(&self, tx_hash: TxHash) -> Result<TxHash> {
+        let tx = self.pending_tx(tx_hash, DEFAULT_CANCEL_BUMP_PCT).await?;
+        let fees = replacement_fees(sent_fees(&tx), DEFAULT_CANCEL_BUMP_PCT, self.gas_fees().await?);
+        let from: Address = tx.inner.signer();
+
+        let mut request = TransactionRequest::default()
+            .from(from)
+            .to(from)
+            .value(U256::ZERO)
+            .nonce(tx.nonce())
+            .gas_limit(TRANSFER_GAS_LIMIT);
+        request.chain_id = tx.chain_id();
+
+        let pending = self.provider.send_transaction(fees.apply(request)).await?;
+
+        Ok(*pending.tx_hash())
+    }
+
+    async fn pending_tx(&self, tx_hash: TxHash, bump_pct: u32) -> Result<Transaction> {
+        if bump_pct < MIN_REPLACEMENT_BUMP_PCT {
+            return Err(FourMemeError::Validation(format!(
+                "Replacement fee bump {}% is below the {}% nodes require", bump_pct, MIN_REPLACEMENT_BUMP_PCT
+            )));
+        }
+
+        let tx = self.provider.get_transaction_by_hash(tx_hash).await?
+            .ok_or(FourMemeError::Dropped(tx_hash))?;
+
+        if tx.block_number.is_some() {
+            return Err(FourMemeError::Validation(format!("Transaction {} is already mined", tx_hash)));
+        }
+
+        Ok(tx)
+    }
+}
+
+impl PendingTrade {
+    /// [`FourMemeSdk::speed_up`] for this trade, returning a handle to the replacement.
+    pub async fn speed_up(&self, bump_pct: u32) -> Result<PendingTrade> {
+        let tx_hash = self.sdk.speed_up(self.tx_hash(), bump_pct).await?;
+        Ok(self.replaced_by(tx_hash))
+    }
+
+    /// [`FourMemeSdk::cancel`] for this trade, returning the hash of the cancelling transfer.
+    pub async fn cancel(&self) -> Result<TxHash> {
+        self.sdk.cancel(self.tx_hash()).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use alloy::{
+        consensus::{transaction::Recovered, SignableTransaction, TxEnvelope, TxLegacy},
+        network::Ethereum,
+        primitives::{Bytes, Signature, TxKind},
+        providers::{DynProvider, PendingTransactionBuilder, RootProvider},
+        transports::{mock::Asserter, TransportResult},
+    };
+
+    use super::*;
+    use crate::test_utils::{mocked_provider, mocked_sdk};
+    use crate::FixedGas;
+
+    const GWEI: u128 = 1_000_000_000;
+
+    /// Keeps every request passed to `send_transaction` before the mocked client sees it.
+    struct Recorder {
+        inner: DynProvider,
+        sent: Arc<Mutex<Vec<TransactionRequest>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for Recorder {
+        fn root(&self) -> &RootProvider {
+            self.inner.root()
+        }
+
+        async fn send_transaction(&self, tx: TransactionRequest) -> TransportResult<PendingTransactionBuilder<Ethereum>> {
+            self.sent.lock().unwrap().push(tx.clone());
+            self.inner.send_transaction(tx).await
+        }
+    }
+
+    fn pending_legacy(tx: TxLegacy) -> Transaction {
+        let signed = tx.into_signed(Signature::new(U256::from(1u64), U256::from(1u64), false));
+        Transaction {
+            inner: Recovered::new_unchecked(TxEnvelope::Legacy(signed), Address::repeat_byte(1)),
+            block_hash: None,
+            block_number: None,
+            transaction_index: None,
+            effective_gas_price: None,
+        }
+    }
+
+    async fn recording_sdk(asserter: Asserter) -> (FourMemeSdk, Arc<Mutex<Vec<TransactionRequest>>>) {
+        let sent = Arc::default();
+        let inner = mocked_provider(asserter);
+        let provider = DynProvider::new(Recorder { inner, sent: Arc::clone(&sent) });
+        let sdk = FourMemeSdk::new_with_provider(provider, None, None).await.unwrap()
+            .with_gas_strategy(FixedGas(GasFees::Legacy { gas_price: GWEI }));
+
+        (sdk, sent)
+    }
+
+    #[test]
+    fn test_replacement_fees_keep_pricing_path() {
+        let legacy = GasFees::Legacy { gas_price: GWEI };
+        assert_eq!(replacement_fees(legacy, 10, legacy), GasFees::Legacy { gas_price: 11 * GWEI / 10 });
+        assert_eq!(
+            replacement_fees(legacy, 10, GasFees::Legacy { gas_price: 3 * GWEI }),
+            GasFees::Legacy { gas_price: 3 * GWEI },
+        );
+
+        let eip1559 = GasFees::Eip1559 { max_fee_per_gas: 2 * GWEI, max_priority_fee_per_gas: GWEI };
+        assert_eq!(
+            replacement_fees(eip1559, 50, GasFees::Legacy { gas_price: 5 * GWEI }),
+            GasFees::Eip1559 { max_fee_per_gas: 5 * GWEI, max_priority_fee_per_gas: 3 * GWEI / 2 },
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replacement_is_validated() {
+        let legacy = TxLegacy { nonce: 3, gas_price: GWEI, gas_limit: 21_000, ..Default::default() };
+        let signed = legacy.into_signed(Signature::new(U256::from(1u64), U256::from(1u64), false));
+        let tx = Transaction {
+            inner: Recovered::new_unchecked(TxEnvelope::Legacy(signed), Address::repeat_byte(1)),
+            block_hash: None,
+            block_number: Some(10),
+            transaction_index: Some(0),
+            effective_gas_price: None,
+        };
+
+        let asserter = Asserter::new();
+        asserter.push_success(&tx);
+        let sdk = mocked_sdk(asserter).await;
+
+        assert!(matches!(sdk.speed_up(TxHash::ZERO, 5).await, Err(FourMemeError::Validation(_))));
+        assert!(matches!(sdk.cancel(TxHash::ZERO).await, Err(FourMemeError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_speed_up_resends_same_call() {
+        let contract = Address::repeat_byte(0x5c);
+        let input = Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]);
+        let original = pending_legacy(TxLegacy {
+            chain_id: Some(56),
+            nonce: 7,
+            gas_price: GWEI,
+            gas_limit: 300_000,
+            to: TxKind::Call(contract),
+            value: U256::from(5u64),
+            input: input.clone(),
+        });
+
+        let asserter = Asserter::new();
+        asserter.push_success(&original);
+        asserter.push_success(&TxHash::repeat_byte(0x22));
+        let (sdk, sent) = recording_sdk(asserter).await;
+
+        assert_eq!(sdk.speed_up(TxHash::ZERO, 25).await.unwrap(), TxHash::repeat_byte(0x22));
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        let request = &sent[0];
+        assert_eq!(request.from, Some(Address::repeat_byte(1)));
+        assert_eq!(request.nonce, Some(7));
+        assert_eq!(request.to, Some(TxKind::Call(contract)));
+        assert_eq!(request.input.input(), Some(&input));
+        assert_eq!(request.value, Some(U256::from(5u64)));
+        assert_eq!(request.gas_price, Some(5 * GWEI / 4));
+        assert!(request.gas_price.unwrap() * 100 >= GWEI * (100 + MIN_REPLACEMENT_BUMP_PCT as u128));
+        assert_eq!(request.max_fee_per_gas, None);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_sends_empty_transfer_to_self() {
+        let original = pending_legacy(TxLegacy {
+            chain_id: Some(56),
+            nonce: 7,
+            gas_price: GWEI,
+            gas_limit: 300_000,
+            to: TxKind::Call(Address::repeat_byte(0x5c)),
+            value: U256::from(5u64),
+            input: Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]),
+        });
+
+        let asserter = Asserter::new();
+        asserter.push_success(&original);
+        asserter.push_success(&TxHash::repeat_byte(0x33));
+        let (sdk, sent) = recording_sdk(asserter).await;
+
+        assert_eq!(sdk.cancel(TxHash::ZERO).await.unwrap(), TxHash::repeat_byte(0x33));
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        let request = &sent[0];
+        assert_eq!(request.from, Some(Address::repeat_byte(1)));
+        assert_eq!(request.to, Some(TxKind::Call(Address::repeat_byte(1))));
+        assert_eq!(request.value, Some(U256::ZERO));
+        assert_eq!(request.nonce, Some(7));
+        assert_eq!(request.gas, Some(TRANSFER_GAS_LIMIT));
+        assert_eq!(request.input.input(), None);
+        assert_eq!(request.gas_price, Some(6 * GWEI / 5));
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt;
+
+use alloy::{
+    eips::BlockId,
+    primitives::{Bytes, Selector, TxHash, U256},
+    providers::Provider,
+    rpc::types::TransactionReceipt,
+    sol_types::{Panic, Revert, SolError},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{FourMemeError, FourMemeSdk, Result};
+
+
+/// A decoded TokenManager revert.
+///
+/// `Error(string)` reasons equal to a known TokenManager check are mapped to a dedicated
+/// variant (the original message is kept); everything else falls through to `Reason`.
+/// The TokenManager ABI declares no custom errors, so any other selector is kept raw in `Custom`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContractRevert {
+    /// `Slippage`: the `minAmount` / `minFunds` / `maxFunds` bound was not met.
+    Slippage(String),
+    /// `Disabled`: trading is halted globally (`_tradingHalt`).
+    TradingHalted(String),
+    /// The token is not in `STATUS_TRADING` (adding liquidity, completed or halted).
+    WrongStatus(String),
+    /// The token's `launchTime` has not been reached yet.
+    NotLaunched(String),
+    /// `More BNB`: `msg.value` does not cover the trade.
+    InsufficientFunds(String),
+    /// Any other `Error(string)` reason.
+    Reason(String),
+    /// `Panic(uint256)` raised by a failed assert or arithmetic check.
+    Panic(U256),
+    /// Revert data with a selector the TokenManager ABI does not know.
+    Custom { selector: Selector, data: Bytes },
+    /// The node reported a revert without any data.
+    Empty,
+}
+
+impl ContractRevert {
+    /// Decodes raw revert data returned by `eth_call` / `eth_estimateGas`.
+    pub fn decode(data: &[u8]) -> Self {
+        if data.len() < 4 {
+            return Self::Empty;
+        }
+
+        if let Ok(revert) = Revert::abi_decode(data) {
+            return Self::from_reason(revert.reason());
+        }
+
+        if let Ok(panic) = Panic::abi_decode(data) {
+            return Self::Panic(panic.code);
+        }
+
+        Self::Custom {
+            selector: Selector::from_slice(&data[..4]),
+            data: Bytes::copy_from_slice(&data[4..]),
+        }
+    }
+
+    /// Classifies a plain revert message, e.g. the part after `execution reverted:`.
+    pub fn from_reason(reason: &str) -> Self {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Self::Empty;
+        }
+
+        // Exact TokenManager `require` messages; anything else is kept as an opaque reason.
+        let variant = match reason {
+            "Slippage" => Self::Slippage,
+            "Disabled" => Self::TradingHalted,
+            "More BNB" => Self::InsufficientFunds,
+            _ => Self::Reason,
+        };
+        variant(reason.to_string())
+    }
+
+    /// The original revert message, if the revert carried one.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Self::Slippage(m)
+            | Self::TradingHalted(m)
+            | Self::WrongStatus(m)
+            | Self::NotLaunched(m)
+            | Self::InsufficientFunds(m)
+            | Self::Reason(m) => Some(m),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ContractRevert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Slippage(m) => write!(f, "slippage: {}", m),
+            Self::TradingHalted(m) => write!(f, "trading halted: {}", m),
+            Self::WrongStatus(m) => write!(f, "wrong token status: {}", m),
+            Self::NotLaunched(m) => write!(f, "not launched: {}", m),
+            Self::InsufficientFunds(m) => write!(f, "insufficient funds: {}", m),
+            Self::Reason(m) => f.write_str(m),
+            Self::Panic(code) => Panic { code: *code }.fmt(f),
+            Self::Custom { selector, data } => write!(f, "custom error {} ({} bytes)", selector, data.len()),
+            Self::Empty => f.write_str("<no reason>"),
+        }
+    }
+}
+
+
+impl FourMemeSdk {
+    /// Explains why a mined transaction failed by replaying it with `eth_call` on the parent block.
+    ///
+    /// Returns `None` if the transaction succeeded. The replay runs without the transactions that
+    /// preceded it in the same block, so state-dependent reverts (slippage) are best effort.
+    pub async fn failed_tx_revert(&self, tx_hash: TxHash) -> Result<Option<ContractRevert>> {
+        let receipt = self.provider.get_transaction_receipt(tx_hash).await?
+            .ok_or_else(|| FourMemeError::Validation(format!("No receipt for transaction {}", tx_hash)))?;
+
+        if receipt.status() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.replay_failed_tx(&receipt).await?.0))
+    }
+
+    /// Replays a failed transaction on its parent block, returning the decoded revert and its raw data.
+    pub(crate) async fn replay_failed_tx(&self, receipt: &TransactionReceipt) -> Result<(ContractRevert, Bytes)> {
+        let tx_hash = receipt.transaction_hash;
+        let tx = self.provider.get_transaction_by_hash(tx_hash).await?
+            .ok_or_else(|| FourMemeError::Validation(format!("Unknown transaction {}", tx_hash)))?;
+
+        let block = receipt.block_number.unwrap_or_default().saturating_sub(1);
+        let request = tx.into_request();
+
+        match self.provider.call(request).block(BlockId::number(block)).await {
+            // The replay did not revert against the parent state; the cause was an earlier tx in the block.
+            Ok(_) => Ok((ContractRevert::Empty, Bytes::new())),
+            Err(e) => match FourMemeError::from(e) {
+                FourMemeError::Revert { reason, data } => Ok((reason, data)),
+                e => Err(e),
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_error_string() {
+        let data = Revert::from("Slippage").abi_encode();
+        assert_eq!(ContractRevert::decode(&data), ContractRevert::Slippage("Slippage".to_string()));
+
+        let data = Revert::from("Disabled").abi_encode();
+        assert_eq!(ContractRevert::decode(&data), ContractRevert::TradingHalted("Disabled".to_string()));
+
+        let data = Revert::from("More BNB").abi_encode();
+        assert_eq!(ContractRevert::decode(&data), ContractRevert::InsufficientFunds("More BNB".to_string()));
+
+        let data = Revert::from("GW").abi_encode();
+        assert_eq!(ContractRevert::decode(&data), ContractRevert::Reason("GW".to_string()));
+    }
+
+    #[test]
+    fn test_unknown_reasons_are_not_guessed() {
+        for reason in ["Slippage: minAmount", "ERC20: transfer amount exceeds balance", "Invalid status", "launch"] {
+            assert_eq!(ContractRevert::from_reason(reason), ContractRevert::Reason(reason.to_string()));
+        }
+        assert_eq!(ContractRevert::from_reason(" Slippage "), ContractRevert::Slippage("Slippage".to_string()));
+    }
+
+    #[test]
+    fn test_decode_panic() {
+        let data = Panic::from(0x11u64).abi_encode();
+        let revert = ContractRevert::decode(&data);
+
+        assert_eq!(revert, ContractRevert::Panic(U256::from(0x11u64)));
+        assert!(revert.to_string().contains("overflow"));
+    }
+
+    #[test]
+    fn test_decode_custom_and_empty() {
+        let revert = ContractRevert::decode(&[0xde, 0xad, 0xbe, 0xef, 0x01]);
+        assert_eq!(revert, ContractRevert::Custom {
+            selector: Selector::from([0xde, 0xad, 0xbe, 0xef]),
+            data: Bytes::from_static(&[0x01]),
+        });
+
+        assert_eq!(ContractRevert::decode(&[]), ContractRevert::Empty);
+        assert_eq!(ContractRevert::from_reason("  "), ContractRevert::Empty);
+    }
+}
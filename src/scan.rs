@@ -0,0 +1,329 @@
+use std::{future::ready, time::Duration};
+
+use alloy::{providers::Provider, rpc::types::Filter, transports::RpcError};
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{FourMemeError, FourMemeEvent, FourMemeSdk, LogPosition, Result, SubscriptionFilter};
+
+
+const DEFAULT_CHUNK_SIZE: u64 = 5_000;
+const DEFAULT_CONCURRENCY: usize = 4;
+const MAX_RETRIES: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+
+/// Where a scan stopped, to resume it later without missing or repeating events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    /// First block that still has to be scanned.
+    pub next_block: u64,
+    /// Last event processed in `next_block`, if the block was only partly processed.
+    pub last: Option<LogPosition>,
+}
+
+impl ScanCheckpoint {
+    pub fn at_block(next_block: u64) -> Self {
+        Self { next_block, last: None }
+    }
+
+    /// Resumes right after `event`.
+    pub fn after(event: &FourMemeEvent) -> Option<Self> {
+        let position = event.meta.position()?;
+        Some(Self { next_block: position.block_number, last: Some(position) })
+    }
+
+    /// Resumes at `event`, so it is processed again.
+    pub fn before(event: &FourMemeEvent) -> Option<Self> {
+        let position = event.meta.position()?;
+        Some(match position.log_index.checked_sub(1) {
+            Some(log_index) => Self { next_block: position.block_number, last: Some(LogPosition { log_index, ..position }) },
+            None => Self::at_block(position.block_number),
+        })
+    }
+
+    fn skips(&self, position: LogPosition) -> bool {
+        self.last.is_some_and(|last| position <= last)
+    }
+}
+
+
+/// The events of one block range, yielded in block order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanChunk {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub events: Vec<FourMemeEvent>,
+}
+
+impl ScanChunk {
+    /// Checkpoint to store once every event of this chunk was processed.
+    pub fn checkpoint(&self) -> ScanCheckpoint {
+        ScanCheckpoint::at_block(self.to_block + 1)
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Blocks per `eth_getLogs` request; halved automatically when the node rejects a window.
+    pub chunk_size: u64,
+    /// Requests in flight at once.
+    pub concurrency: usize,
+    pub resume_from: Option<ScanCheckpoint>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+            resume_from: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Starts at the checkpoint instead of `from_block`.
+    pub fn resume_from(mut self, checkpoint: ScanCheckpoint) -> Self {
+        self.resume_from = Some(checkpoint);
+        self
+    }
+}
+
+
+/// Whether the node refused an `eth_getLogs` call because the window or result set was too large.
+fn is_range_error(e: &FourMemeError) -> bool {
+    let FourMemeError::Transport(RpcError::ErrorResp(payload)) = e else {
+        return false;
+    };
+
+    match payload.code {
+        // Invalid params: the filter is ours, so only its range can be at fault.
+        -32602 => true,
+        // Limit exceeded (EIP-1474) and the generic server error also cover rate limits and
+        // other failures: only the message tells a rejected range apart.
+        -32005 | -32000 => {
+            let msg = payload.message.to_ascii_lowercase();
+            ["block range", "more than", "too many", "too large", "response size"]
+                .iter()
+                .any(|pattern| msg.contains(pattern))
+        }
+        _ => false,
+    }
+}
+
+impl FourMemeSdk {
+    /// Historical TokenManager events in `from_block..=to_block`, oldest first.
+    pub fn scan_events(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        filter: SubscriptionFilter,
+    ) -> impl Stream<Item = Result<FourMemeEvent>> + Send + 'static {
+        self.scan_events_with(from_block, to_block, filter, ScanOptions::default())
+    }
+
+    pub fn scan_events_with(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        filter: SubscriptionFilter,
+        options: ScanOptions,
+    ) -> impl Stream<Item = Result<FourMemeEvent>> + Send + 'static {
+        self.scan_event_chunks(from_block, to_block, filter, options)
+            .flat_map(|chunk| stream::iter(match chunk {
+                Ok(chunk) => chunk.events.into_iter().map(Ok).collect::<Vec<_>>(),
+                Err(e) => vec![Err(e)],
+            }))
+    }
+
+    /// Like [`scan_events_with`](Self::scan_events_with), but yields whole block ranges so the
+    /// caller can store a [`ScanCheckpoint`] after each one, even when it had no events.
+    ///
+    /// Ranges are fetched `concurrency` at a time and yielded in order; the stream ends after
+    /// the first error.
+    pub fn scan_event_chunks(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        filter: SubscriptionFilter,
+        options: ScanOptions,
+    ) -> impl Stream<Item = Result<ScanChunk>> + Send + 'static {
+        let checkpoint = options.resume_from.unwrap_or(ScanCheckpoint::at_block(from_block));
+        let chunk_size = options.chunk_size.max(1);
+
+        let ranges = (checkpoint.next_block..=to_block)
+            .step_by(usize::try_from(chunk_size).unwrap_or(usize::MAX))
+            .map(move |from| (from, to_block.min(from.saturating_add(chunk_size - 1))));
+
+        let sdk = self.clone();
+        let log_filter = filter.log_filter(*self.contract.address());
+
+        stream::iter(ranges)
+            .map(move |(from, to)| {
+                let (sdk, filter, log_filter) = (sdk.clone(), filter.clone(), log_filter.clone());
+                async move {
+                    let events = sdk.fetch_events(&log_filter, from, to).await?
+                        .into_iter()
+                        .filter(|event| filter.matches(&event.event))
+                        .filter(|event| !event.meta.position().is_some_and(|position| checkpoint.skips(position)))
+                        .collect();
+                    Ok(ScanChunk { from_block: from, to_block: to, events })
+                }
+            })
+            .buffered(options.concurrency.max(1))
+            .scan(false, |failed, chunk: Result<ScanChunk>| {
+                if *failed {
+                    return ready(None);
+                }
+                *failed = chunk.is_err();
+                ready(Some(chunk))
+            })
+    }
+
+    /// Decoded events in `from..=to`, splitting the range whenever the node rejects it as too large.
+    async fn fetch_events(&self, filter: &Filter, from: u64, to: u64) -> Result<Vec<FourMemeEvent>> {
+        let mut pending = vec![(from, to)];
+        let mut events = Vec::new();
+        let mut retries = 0;
+
+        while let Some((from, to)) = pending.pop() {
+            let logs = match self.provider.get_logs(&filter.clone().from_block(from).to_block(to)).await {
+                Ok(logs) => {
+                    retries = 0;
+                    logs
+                }
+                Err(e) => {
+                    let e = FourMemeError::from(e);
+                    // Checked first: some range rejections share their code with rate limits.
+                    if from < to && is_range_error(&e) {
+                        let mid = from + (to - from) / 2;
+                        // Popped from the back: the lower half goes first.
+                        pending.push((mid + 1, to));
+                        pending.push((from, mid));
+                        continue;
+                    }
+                    if e.is_retryable() && retries < MAX_RETRIES {
+                        retries += 1;
+                        tokio::time::sleep(RETRY_DELAY * retries).await;
+                        pending.push((from, to));
+                        continue;
+                    }
+                    return Err(e);
+                }
+            };
+
+            for log in &logs {
+                let event = FourMemeEvent::decode_log(log).ok_or_else(|| FourMemeError::Abi(format!(
+                    "Malformed TokenManager log {:?} in transaction {:?}", log.log_index, log.transaction_hash
+                )))?;
+                events.push(event);
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{rpc::json_rpc::ErrorPayload, transports::mock::Asserter};
+
+    use super::*;
+    use crate::test_utils::{mocked_sdk, purchase_log};
+
+    fn error_payload(code: i64, message: &str) -> ErrorPayload {
+        ErrorPayload { code, message: message.to_string().into(), data: None }
+    }
+
+    fn positions(events: &[Result<FourMemeEvent>]) -> Vec<(u64, u64)> {
+        events.iter()
+            .map(|event| event.as_ref().unwrap().meta.position().unwrap())
+            .map(|p| (p.block_number, p.log_index))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_splits_rejected_ranges_in_order() {
+        let asserter = Asserter::new();
+        asserter.push_failure(error_payload(-32005, "query returned more than 10000 results")); // 0..=9
+        asserter.push_success(&vec![purchase_log(2, 0)]); // 0..=4
+        asserter.push_success(&vec![purchase_log(7, 0), purchase_log(7, 1)]); // 5..=9
+        asserter.push_success(&vec![purchase_log(12, 0)]); // 10..=14
+
+        let options = ScanOptions::default().with_chunk_size(10).with_concurrency(1);
+        let events: Vec<_> = mocked_sdk(asserter).await
+            .scan_events_with(0, 14, SubscriptionFilter::new(), options)
+            .collect()
+            .await;
+
+        assert_eq!(positions(&events), vec![(2, 0), (7, 0), (7, 1), (12, 0)]);
+    }
+
+    #[test]
+    fn test_range_errors_are_keyed_on_the_code() {
+        let error = |code, message: &str| FourMemeError::from(RpcError::ErrorResp(error_payload(code, message)));
+
+        assert!(is_range_error(&error(-32602, "Log response size exceeded")));
+        assert!(is_range_error(&error(-32000, "exceed maximum block range: 5000")));
+        assert!(!is_range_error(&error(-32005, "rate limit exceeded")));
+        assert!(!is_range_error(&error(-32603, "query returned more than 10000 results")));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_reset_after_each_success() {
+        let asserter = Asserter::new();
+        let rate_limited = || error_payload(429, "Too Many Requests");
+        asserter.push_failure(error_payload(-32602, "block range too large")); // 0..=9
+        for _ in 0..MAX_RETRIES {
+            asserter.push_failure(rate_limited()); // 0..=4
+        }
+        asserter.push_success(&vec![purchase_log(2, 0)]);
+        for _ in 0..MAX_RETRIES {
+            asserter.push_failure(rate_limited()); // 5..=9
+        }
+        asserter.push_success(&vec![purchase_log(7, 0)]);
+
+        let options = ScanOptions::default().with_chunk_size(10).with_concurrency(1);
+        let events: Vec<_> = mocked_sdk(asserter).await
+            .scan_events_with(0, 9, SubscriptionFilter::new(), options)
+            .collect()
+            .await;
+
+        assert_eq!(positions(&events), vec![(2, 0), (7, 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_resumes_from_checkpoint_and_stops_on_error() {
+        let asserter = Asserter::new();
+        asserter.push_success(&vec![purchase_log(5, 0), purchase_log(5, 1), purchase_log(6, 0)]);
+        asserter.push_failure_msg("invalid params");
+
+        let checkpoint = ScanCheckpoint { next_block: 5, last: Some(LogPosition { block_number: 5, log_index: 0 }) };
+        let options = ScanOptions::default().with_chunk_size(5).with_concurrency(2).resume_from(checkpoint);
+        let chunks: Vec<_> = mocked_sdk(asserter).await
+            .scan_event_chunks(0, 20, SubscriptionFilter::new(), options)
+            .collect()
+            .await;
+
+        assert_eq!(chunks.len(), 2);
+        let first = chunks[0].as_ref().unwrap();
+        assert_eq!((first.from_block, first.to_block), (5, 9));
+        assert_eq!(first.events.len(), 2);
+        assert_eq!(first.checkpoint(), ScanCheckpoint::at_block(10));
+        assert_eq!(ScanCheckpoint::after(&first.events[1]).unwrap().last, Some(LogPosition { block_number: 6, log_index: 0 }));
+        assert!(chunks[1].is_err());
+    }
+}
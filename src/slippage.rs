@@ -0,0 +1,203 @@
+use alloy::primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::{BondingCurve, BuyAmapParams, FourMemeError, FourMemeSdk, PendingTrade, Result, SellAmapParams, TradeQuote, TradeSide};
+
+
+const MAX_BPS: u32 = 10_000;
+
+
+/// How a slippage-bounded trade was priced, returned next to the transaction for auditing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlippageDecision {
+    pub token: Address,
+    pub side: TradeSide,
+    /// Funds sent (buy) or tokens sold (sell).
+    pub input: U256,
+    /// Expected outcome at the curve state that was read.
+    pub quote: TradeQuote,
+    pub slippage_bps: u32,
+    /// `minAmount` (buy) or `minFunds` (sell) passed to the contract.
+    pub limit: U256,
+    /// `offers` / `funds` of the curve the quote was computed from.
+    pub offers: U256,
+    pub funds: U256,
+}
+
+impl SlippageDecision {
+    /// Prices a `buyTokenAMAP` of `funds`: the fee comes off the top, the rest buys on the curve.
+    pub fn for_buy(token: Address, curve: &BondingCurve, funds: U256, fee: U256, slippage_bps: u32) -> Result<Self> {
+        check_bps(slippage_bps)?;
+
+        let spend = funds.checked_sub(fee)
+            .ok_or_else(|| FourMemeError::Validation(format!("Funds {} do not cover the trading fee {}", funds, fee)))?;
+        let amount = curve.calc_buy_amount(spend)
+            .ok_or_else(|| FourMemeError::Validation(format!("Invalid curve state for token {}", token)))?
+            .min(curve.offers);
+
+        Ok(Self {
+            token,
+            side: TradeSide::Buy,
+            input: funds,
+            quote: TradeQuote { amount, cost: spend, fee },
+            slippage_bps,
+            limit: apply_bps(amount, slippage_bps),
+            offers: curve.offers,
+            funds: curve.funds,
+        })
+    }
+
+    /// Prices a `sellToken` of `amount`: the curve pays out, then the fee is taken from the proceeds.
+    pub fn for_sell(token: Address, curve: &BondingCurve, amount: U256, fee: U256, slippage_bps: u32) -> Result<Self> {
+        check_bps(slippage_bps)?;
+
+        let cost = curve.calc_sell_cost(amount)
+            .ok_or_else(|| FourMemeError::Validation(format!("Invalid curve state for token {}", token)))?;
+
+        Ok(Self {
+            token,
+            side: TradeSide::Sell,
+            input: amount,
+            quote: TradeQuote { amount, cost, fee },
+            slippage_bps,
+            limit: apply_bps(cost.saturating_sub(fee), slippage_bps),
+            offers: curve.offers,
+            funds: curve.funds,
+        })
+    }
+}
+
+fn check_bps(slippage_bps: u32) -> Result<()> {
+    if slippage_bps > MAX_BPS {
+        return Err(FourMemeError::Validation(format!("Slippage {} bps exceeds 100%", slippage_bps)));
+    }
+    Ok(())
+}
+
+fn apply_bps(value: U256, slippage_bps: u32) -> U256 {
+    value * U256::from(MAX_BPS - slippage_bps) / U256::from(MAX_BPS)
+}
+
+
+impl FourMemeSdk {
+    /// Reads the curve and the trading fee, and prices a buy of `funds` with the given tolerance.
+    pub async fn quote_buy_with_slippage(&self, token: Address, funds: U256, slippage_bps: u32) -> Result<SlippageDecision> {
+        let ti = self.token_info(token).await?;
+        let fee = self.calc_trading_fee(ti.clone(), funds).await?;
+
+        SlippageDecision::for_buy(token, &BondingCurve::from(&ti), funds, fee, slippage_bps)
+    }
+
+    /// Reads the curve and the trading fee, and prices a sale of `amount` with the given tolerance.
+    pub async fn quote_sell_with_slippage(&self, token: Address, amount: U256, slippage_bps: u32) -> Result<SlippageDecision> {
+        let ti = self.token_info(token).await?;
+        let curve = BondingCurve::from(&ti);
+        let cost = curve.calc_sell_cost(amount)
+            .ok_or_else(|| FourMemeError::Validation(format!("Invalid curve state for token {}", token)))?;
+        let fee = self.calc_trading_fee(ti, cost).await?;
+
+        SlippageDecision::for_sell(token, &curve, amount, fee, slippage_bps)
+    }
+
+    /// `buyTokenAMAP` with `minAmount` derived from a fresh quote minus `slippage_bps`, for `to`
+    /// or the sender.
+    pub async fn buy_with_slippage(
+        &self,
+        token: Address,
+        funds: U256,
+        slippage_bps: u32,
+        to: Option<Address>,
+    ) -> Result<(SlippageDecision, PendingTrade)> {
+        let decision = self.quote_buy_with_slippage(token, funds, slippage_bps).await?;
+
+        let pending = self.buy_token_amap(BuyAmapParams {
+            token,
+            funds,
+            min_amount: decision.limit,
+            to,
+        }).await?;
+
+        Ok((decision, pending))
+    }
+
+    /// `sellToken` with `minFunds` derived from a fresh quote (net of fee) minus `slippage_bps`.
+    pub async fn sell_with_slippage(
+        &self,
+        token: Address,
+        amount: U256,
+        slippage_bps: u32,
+        user_address: Address,
+    ) -> Result<(SlippageDecision, PendingTrade)> {
+        let decision = self.quote_sell_with_slippage(token, amount, slippage_bps).await?;
+
+        let pending = self.sell_token_amap(SellAmapParams {
+            token,
+            amount,
+            min_funds: Some(decision.limit),
+            origin: None,
+            from: None,
+            fee_rate: None,
+            fee_recipient: None,
+        }, user_address).await?;
+
+        Ok((decision, pending))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve() -> BondingCurve {
+        let unit = U256::from(1_000_000_000_000_000_000u128);
+        let t = U256::from(1_066_666_666u64) * unit;
+        BondingCurve {
+            k: U256::from(8u64) * unit * t,
+            t,
+            offers: U256::from(800_000_000u64) * unit,
+            funds: U256::ZERO,
+            max_offers: U256::from(800_000_000u64) * unit,
+            max_raising: U256::from(24u64) * unit,
+        }
+    }
+
+    #[test]
+    fn test_buy_decision_applies_fee_and_bps() {
+        let curve = curve();
+        let funds = U256::from(1_000_000_000_000_000_000u128);
+        let fee = funds / U256::from(100u64);
+
+        let decision = SlippageDecision::for_buy(Address::ZERO, &curve, funds, fee, 50).unwrap();
+        let expected = curve.calc_buy_amount(funds - fee).unwrap();
+
+        assert_eq!(decision.quote.amount, expected);
+        assert_eq!(decision.quote.cost, funds - fee);
+        assert_eq!(decision.limit, expected * U256::from(9_950u64) / U256::from(10_000u64));
+    }
+
+    #[test]
+    fn test_sell_decision_uses_net_proceeds() {
+        let curve = curve().after_buy(U256::from(10u64).pow(U256::from(25u64)), U256::ZERO).unwrap();
+        let amount = U256::from(10u64).pow(U256::from(24u64));
+        let cost = curve.calc_sell_cost(amount).unwrap();
+        let fee = cost / U256::from(100u64);
+
+        let decision = SlippageDecision::for_sell(Address::ZERO, &curve, amount, fee, 0).unwrap();
+        assert_eq!(decision.limit, cost - fee);
+        assert_eq!(decision.side, TradeSide::Sell);
+    }
+
+    #[test]
+    fn test_invalid_inputs_are_rejected() {
+        let curve = curve();
+        assert!(matches!(
+            SlippageDecision::for_buy(Address::ZERO, &curve, U256::from(1u64), U256::ZERO, 10_001),
+            Err(FourMemeError::Validation(_))
+        ));
+        assert!(matches!(
+            SlippageDecision::for_buy(Address::ZERO, &curve, U256::from(1u64), U256::from(2u64), 100),
+            Err(FourMemeError::Validation(_))
+        ));
+    }
+}
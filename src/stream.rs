@@ -0,0 +1,209 @@
+use std::{future::Future, sync::Arc};
+
+use futures::{stream, Stream};
+use tokio::{
+    sync::{broadcast, watch},
+    task::JoinHandle,
+};
+
+use crate::{FourMemeError, FourMemeEvent, FourMemeSdk, Result, SubscriptionOptions};
+
+
+type SharedItem = std::result::Result<FourMemeEvent, Arc<FourMemeError>>;
+
+
+/// Stops a subscription started with [`FourMemeSdk::event_stream`] or [`FourMemeSdk::event_broadcast`].
+///
+/// Dropping the handle does not stop anything: the subscription then runs until its consumers are gone.
+#[derive(Debug)]
+pub struct SubscriptionHandle {
+    cancel: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl SubscriptionHandle {
+    /// Runs `task` until it returns or the handle cancels it.
+    pub(crate) fn spawn(task: impl Future<Output = ()> + Send + 'static) -> Self {
+        let (cancel, mut cancelled) = watch::channel(false);
+        let task = tokio::spawn(async move {
+            tokio::select! {
+                _ = task => {}
+                // Resolves once cancelled; never if the handle was dropped instead.
+                Ok(_) = cancelled.wait_for(|cancelled| *cancelled) => {}
+            }
+        });
+
+        Self { cancel, task }
+    }
+
+    /// Stops fetching new events. Events already queued are still delivered, then the streams end.
+    pub fn cancel(&self) {
+        self.cancel.send_replace(true);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Cancels the subscription and waits until its background task has exited.
+    pub async fn shutdown(self) {
+        self.cancel();
+        if let Err(e) = self.task.await {
+            if e.is_panic() {
+                std::panic::resume_unwind(e.into_panic());
+            }
+        }
+    }
+
+    /// The task alone, which then runs until its receiver is dropped.
+    pub(crate) fn into_task(self) -> JoinHandle<()> {
+        self.task
+    }
+}
+
+
+/// Hands out consumers of a [`FourMemeSdk::event_broadcast`] subscription.
+#[derive(Debug, Clone)]
+pub struct EventBroadcast {
+    sender: broadcast::WeakSender<SharedItem>,
+}
+
+impl EventBroadcast {
+    /// A new consumer, receiving the events delivered from now on.
+    ///
+    /// A consumer that falls more than `channel_capacity` events behind gets one
+    /// [`FourMemeError::Lagged`] with the number it missed and continues with the oldest event
+    /// still queued. The stream ends once the subscription stopped and the queue is drained.
+    pub fn subscribe(&self) -> impl Stream<Item = Result<FourMemeEvent>> + Send + 'static {
+        Self::consumer(self.sender.upgrade().map(|sender| sender.subscribe()))
+    }
+
+    fn consumer(rx: Option<broadcast::Receiver<SharedItem>>) -> impl Stream<Item = Result<FourMemeEvent>> + Send + 'static {
+        stream::unfold(rx, |rx| async move {
+            let mut rx = rx?;
+            let item = match rx.recv().await {
+                Ok(item) => item.map_err(FourMemeError::Shared),
+                Err(broadcast::error::RecvError::Lagged(missed)) => Err(FourMemeError::Lagged(missed)),
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            Some((item, Some(rx)))
+        })
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        self.sender.upgrade().map_or(0, |sender| sender.receiver_count())
+    }
+}
+
+
+impl FourMemeSdk {
+    /// [`subscribe_events_with`](Self::subscribe_events_with) as a `Stream` for a single consumer.
+    ///
+    /// While the consumer is more than `channel_capacity` events behind, the follower stops
+    /// fetching; it resumes from its block cursor, so nothing is lost.
+    pub async fn event_stream(
+        &self,
+        options: SubscriptionOptions,
+    ) -> Result<(SubscriptionHandle, impl Stream<Item = Result<FourMemeEvent>> + Send + 'static)> {
+        let (handle, rx) = self.spawn_follower(options).await?;
+        let events = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|item| (item, rx)) });
+
+        Ok((handle, events))
+    }
+
+    /// Fans one subscription out to any number of consumers, see [`EventBroadcast::subscribe`].
+    ///
+    /// The first consumer is returned along with the broadcast and subscribed before the
+    /// follower starts, so it sees every event. Consumers never slow each other or the follower
+    /// down; events delivered while nobody is subscribed are dropped.
+    pub async fn event_broadcast(
+        &self,
+        options: SubscriptionOptions,
+    ) -> Result<(SubscriptionHandle, EventBroadcast, impl Stream<Item = Result<FourMemeEvent>> + Send + 'static)> {
+        let (sender, first) = broadcast::channel(options.channel_capacity);
+        let (follower, mut rx) = self.spawn_follower(options).await?;
+        let SubscriptionHandle { cancel, task: _ } = follower;
+
+        let events = EventBroadcast { sender: sender.downgrade() };
+        // Ends when the follower does, after forwarding what it queued.
+        let task = tokio::spawn(async move {
+            while let Some(item) = rx.recv().await {
+                let _ = sender.send(item.map_err(Arc::new));
+            }
+        });
+
+        Ok((SubscriptionHandle { cancel, task }, events, EventBroadcast::consumer(Some(first))))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use alloy::{rpc::types::Log, transports::mock::Asserter};
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::test_utils::{mocked_sdk, purchase_log};
+
+    async fn sdk(logs: Vec<Log>) -> FourMemeSdk {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x5");
+        asserter.push_success(&logs);
+
+        mocked_sdk(asserter).await
+    }
+
+    fn options() -> SubscriptionOptions {
+        SubscriptionOptions::default().from_block(5).with_poll_interval(Duration::from_secs(3600))
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_after_shutdown() {
+        let sdk = sdk(vec![purchase_log(5, 0), purchase_log(5, 1)]).await;
+        let (handle, events) = sdk.event_stream(options()).await.unwrap();
+        let mut events = Box::pin(events);
+
+        assert_eq!(events.next().await.unwrap().unwrap().meta.log_index, Some(0));
+        handle.shutdown().await;
+
+        // Queued events are still delivered.
+        assert_eq!(events.next().await.unwrap().unwrap().meta.log_index, Some(1));
+        assert!(events.next().await.is_none());
+    }
+
+    // With time paused, the sleep only elapses once the follower is idle on its poll interval.
+    #[tokio::test(start_paused = true)]
+    async fn test_broadcast_reports_lagging_consumers() {
+        let sdk = sdk((0..4).map(|index| purchase_log(5, index)).collect()).await;
+        let (handle, broadcast, first) = sdk.event_broadcast(options().with_channel_capacity(2)).await.unwrap();
+        let consumers = [first.boxed(), broadcast.subscribe().boxed()];
+        assert_eq!(broadcast.receiver_count(), 2);
+
+        // Let all four events through before anyone reads.
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        handle.shutdown().await;
+        for consumer in consumers {
+            let received: Vec<_> = consumer.collect().await;
+            assert!(matches!(received[0], Err(FourMemeError::Lagged(2))));
+            let rest: Vec<_> = received[1..].iter().map(|event| event.as_ref().unwrap().meta.log_index).collect();
+            assert_eq!(rest, vec![Some(2), Some(3)]);
+        }
+        assert_eq!(broadcast.receiver_count(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_broadcast_first_consumer_sees_every_event() {
+        let sdk = sdk(vec![purchase_log(5, 0), purchase_log(5, 1)]).await;
+        let (handle, broadcast, first) = sdk.event_broadcast(options()).await.unwrap();
+
+        // Let the follower forward both events before reading.
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(broadcast.receiver_count(), 1);
+        handle.shutdown().await;
+
+        let received: Vec<_> = first.map(|event| event.unwrap().meta.log_index).collect().await;
+        assert_eq!(received, vec![Some(0), Some(1)]);
+    }
+}
@@ -0,0 +1,711 @@
+use std::{collections::{BTreeMap, HashMap}, time::Duration};
+
+use alloy::{
+    primitives::B256,
+    providers::Provider,
+    rpc::types::{Filter, Log},
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::{FourMemeError, FourMemeEvent, FourMemeSdk, Result, SubscriptionFilter, SubscriptionHandle};
+
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(3);
+const DEFAULT_MAX_BLOCK_RANGE: u64 = 2_000;
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+
+/// Where a log sits in the chain; logs are delivered in increasing position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct LogPosition {
+    pub block_number: u64,
+    pub log_index: u64,
+}
+
+impl LogPosition {
+    /// `None` for pending logs, which have no position yet.
+    pub fn of(log: &Log) -> Option<Self> {
+        Some(Self { block_number: log.block_number?, log_index: log.log_index? })
+    }
+}
+
+
+/// Settings of [`FourMemeSdk::subscribe_events_with`].
+#[derive(Debug, Clone)]
+pub struct SubscriptionOptions {
+    /// First block to deliver events from; `None` starts at the next block.
+    pub from_block: Option<u64>,
+    pub poll_interval: Duration,
+    /// Largest `eth_getLogs` window used while catching up.
+    pub max_block_range: u64,
+    /// First delay after an RPC failure; doubled on each consecutive failure.
+    pub retry_delay: Duration,
+    pub channel_capacity: usize,
+    /// Fetch the block timestamp for events whose log does not carry one.
+    pub fetch_timestamps: bool,
+    pub filter: SubscriptionFilter,
+    /// Number of recent blocks whose hashes are checked for reorganizations; 0 disables it.
+    pub reorg_depth: u64,
+    /// Blocks that must be built on top of a block before its events are delivered.
+    pub confirmations: u64,
+    /// Use `eth_subscribe` when the transport supports it (WebSocket, IPC).
+    pub pubsub: bool,
+}
+
+impl Default for SubscriptionOptions {
+    fn default() -> Self {
+        Self {
+            from_block: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            max_block_range: DEFAULT_MAX_BLOCK_RANGE,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            fetch_timestamps: false,
+            filter: SubscriptionFilter::default(),
+            reorg_depth: 0,
+            confirmations: 0,
+            pubsub: true,
+        }
+    }
+}
+
+impl SubscriptionOptions {
+    /// Resumes from `block`, e.g. the block of the last event processed before a restart.
+    /// Events already delivered in that block are delivered again.
+    pub fn from_block(mut self, block: u64) -> Self {
+        self.from_block = Some(block);
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn with_max_block_range(mut self, max_block_range: u64) -> Self {
+        self.max_block_range = max_block_range.max(1);
+        self
+    }
+
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    pub fn with_channel_capacity(mut self, channel_capacity: usize) -> Self {
+        self.channel_capacity = channel_capacity.max(1);
+        self
+    }
+
+    pub fn with_timestamps(mut self, fetch_timestamps: bool) -> Self {
+        self.fetch_timestamps = fetch_timestamps;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: SubscriptionFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Tracks the hashes of the last `depth` blocks and re-sends the events of replaced blocks
+    /// with `meta.removed` set, newest first, before delivering the new chain's events.
+    pub fn with_reorg_depth(mut self, depth: u64) -> Self {
+        self.reorg_depth = depth;
+        self
+    }
+
+    /// Delays delivery until a block has `confirmations` blocks on top of it.
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// `false` polls with `eth_getLogs` even over WebSocket or IPC.
+    pub fn with_pubsub(mut self, pubsub: bool) -> Self {
+        self.pubsub = pubsub;
+        self
+    }
+}
+
+
+/// Follows TokenManager logs from a block cursor, rescanning ranges after failures and reorgs.
+struct LogFollower {
+    sdk: FourMemeSdk,
+    pubsub: bool,
+    filter: Filter,
+    options: SubscriptionOptions,
+    next_block: u64,
+    last: Option<LogPosition>,
+    /// Earliest log the node reported removed; positions from here on are delivered again.
+    reorged_from: Option<LogPosition>,
+    /// Timestamp of the most recent block an event was delivered from.
+    last_timestamp: Option<(u64, u64)>,
+    blocks: BTreeMap<u64, B256>,
+    delivered: BTreeMap<u64, Vec<FourMemeEvent>>,
+}
+
+impl LogFollower {
+    async fn run(mut self, tx: mpsc::Sender<Result<FourMemeEvent>>) {
+        let mut delay = self.options.retry_delay;
+
+        loop {
+            let followed = match self.pubsub {
+                true => self.follow_subscription(&tx).await,
+                false => self.catch_up(&tx).await,
+            };
+            match followed {
+                Ok(true) => {
+                    delay = self.options.retry_delay;
+                    tokio::time::sleep(self.options.poll_interval).await;
+                }
+                Ok(false) => return,
+                Err(e) => {
+                    // Report the failure so a stalled node does not go unnoticed, then retry.
+                    if tx.send(Err(e)).await.is_err() {
+                        return;
+                    }
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RETRY_DELAY);
+                }
+            }
+        }
+    }
+
+    /// Delivers every log up to the current head. Returns `false` once the receiver is gone.
+    async fn catch_up(&mut self, tx: &mpsc::Sender<Result<FourMemeEvent>>) -> Result<bool> {
+        let head = self.sdk.provider.get_block_number().await?
+            .saturating_sub(self.options.confirmations);
+
+        if !self.unwind_reorg(tx).await? {
+            return Ok(false);
+        }
+
+        while self.next_block <= head {
+            let to = head.min(self.next_block + self.options.max_block_range - 1);
+            let filter = self.filter.clone().from_block(self.next_block).to_block(to);
+            let logs = self.sdk.provider.get_logs(&filter).await?;
+
+            for log in logs {
+                if !self.deliver(log, tx).await? {
+                    return Ok(false);
+                }
+            }
+            self.next_block = to + 1;
+            self.track_block(to).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Delivers logs pushed by the node until the subscription closes.
+    async fn follow_subscription(&mut self, tx: &mpsc::Sender<Result<FourMemeEvent>>) -> Result<bool> {
+        if self.options.confirmations > 0 {
+            let mut heads = self.sdk.provider.subscribe_blocks().await?.into_stream();
+            if !self.catch_up(tx).await? {
+                return Ok(false);
+            }
+            while heads.next().await.is_some() {
+                if !self.catch_up(tx).await? {
+                    return Ok(false);
+                }
+            }
+            return Ok(true);
+        }
+
+        let mut logs = self.sdk.provider.subscribe_logs(&self.filter).await?.into_stream();
+        if !self.catch_up(tx).await? {
+            return Ok(false);
+        }
+        while let Some(log) = logs.next().await {
+            if !self.deliver(log, tx).await? {
+                return Ok(false);
+            }
+            // Later logs of the last block may still arrive; `last` drops the repeated ones.
+            if let Some(last) = self.last {
+                self.next_block = self.next_block.max(last.block_number);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Records the hash of the last scanned block and forgets blocks below the reorg depth.
+    async fn track_block(&mut self, block_number: u64) -> Result<()> {
+        if self.options.reorg_depth == 0 {
+            return Ok(());
+        }
+        if let Some(hash) = self.block_hash(block_number).await? {
+            self.blocks.insert(block_number, hash);
+        }
+
+        let oldest = block_number.saturating_sub(self.options.reorg_depth) + 1;
+        self.blocks = self.blocks.split_off(&oldest);
+        self.delivered = self.delivered.split_off(&oldest);
+        Ok(())
+    }
+
+    /// Detects replaced blocks, sends their events back as removed and rewinds the cursor.
+    async fn unwind_reorg(&mut self, tx: &mpsc::Sender<Result<FourMemeEvent>>) -> Result<bool> {
+        let Some((&newest, &hash)) = self.blocks.last_key_value() else {
+            return Ok(true);
+        };
+        if self.block_hash(newest).await? == Some(hash) {
+            return Ok(true);
+        }
+
+        // Below the tracked window the chain is assumed final.
+        let mut fork = self.blocks.first_key_value().map_or(0, |(&n, _)| n.saturating_sub(1));
+        let tracked: Vec<_> = self.blocks.iter().rev().skip(1).map(|(&n, &h)| (n, h)).collect();
+        for (number, hash) in tracked {
+            if self.block_hash(number).await? == Some(hash) {
+                fork = number;
+                break;
+            }
+        }
+
+        self.blocks.split_off(&(fork + 1));
+        let removed = self.delivered.split_off(&(fork + 1));
+        self.next_block = self.next_block.min(fork + 1);
+        self.last = self.last.map(|last| last.min(LogPosition { block_number: fork, log_index: u64::MAX }));
+
+        for mut event in removed.into_values().rev().flat_map(|events| events.into_iter().rev()) {
+            event.meta.removed = true;
+            if tx.send(Ok(event)).await.is_err() {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    async fn block_hash(&self, block_number: u64) -> Result<Option<B256>> {
+        let block = self.sdk.provider.get_block_by_number(block_number.into()).await?;
+        Ok(block.map(|block| block.header.hash))
+    }
+
+    async fn deliver(&mut self, log: Log, tx: &mpsc::Sender<Result<FourMemeEvent>>) -> Result<bool> {
+        let Some(position) = LogPosition::of(&log) else {
+            return Ok(true);
+        };
+        if log.removed {
+            return self.deliver_removed(&log, position, tx).await;
+        }
+        let replayed = self.reorged_from.is_some_and(|from| position >= from);
+        if !replayed && self.last.is_some_and(|last| position <= last) {
+            return Ok(true);
+        }
+
+        let mut event = FourMemeEvent::decode_log(&log).ok_or_else(|| FourMemeError::Abi(format!(
+            "Malformed TokenManager log {} in transaction {:?}", position.log_index, log.transaction_hash
+        )));
+
+        if event.as_ref().is_ok_and(|event| !self.options.filter.matches(&event.event)) {
+            self.advance(position);
+            return Ok(true);
+        }
+
+        if let Ok(event) = &mut event {
+            if self.options.fetch_timestamps && event.meta.timestamp.is_none() {
+                event.meta.timestamp = Some(self.block_timestamp(position.block_number).await?);
+            }
+        }
+
+        // Only mark the log delivered once nothing can fail before sending it.
+        self.advance(position);
+        if let Ok(event) = &event {
+            self.track_delivered(&log, event);
+        }
+        Ok(tx.send(event).await.is_ok())
+    }
+
+    fn advance(&mut self, position: LogPosition) {
+        if self.last.is_none_or(|last| position > last) {
+            self.last = Some(position);
+            self.reorged_from = None;
+        }
+    }
+
+    fn track_delivered(&mut self, log: &Log, event: &FourMemeEvent) {
+        if self.options.reorg_depth == 0 {
+            return;
+        }
+        let (Some(block_number), Some(block_hash)) = (log.block_number, log.block_hash) else {
+            return;
+        };
+
+        self.blocks.insert(block_number, block_hash);
+        self.delivered.entry(block_number).or_default().push(event.clone());
+    }
+
+    /// Forwards a log the node reports as reverted, if its event was delivered before.
+    async fn deliver_removed(
+        &mut self,
+        log: &Log,
+        position: LogPosition,
+        tx: &mpsc::Sender<Result<FourMemeEvent>>,
+    ) -> Result<bool> {
+        if self.last.is_none_or(|last| position > last) {
+            return Ok(true);
+        }
+        let Some(event) = FourMemeEvent::decode_log(log) else {
+            return Ok(true);
+        };
+        if !self.options.filter.matches(&event.event) {
+            return Ok(true);
+        }
+
+        if let Some(events) = self.delivered.get_mut(&position.block_number) {
+            events.retain(|e| e.meta.log_index != Some(position.log_index));
+        }
+        // The replacing blocks reuse these positions.
+        self.reorged_from = Some(self.reorged_from.map_or(position, |from| from.min(position)));
+        self.next_block = self.next_block.min(position.block_number);
+        Ok(tx.send(Ok(event)).await.is_ok())
+    }
+
+    async fn block_timestamp(&mut self, block_number: u64) -> Result<u64> {
+        if let Some((number, timestamp)) = self.last_timestamp {
+            if number == block_number {
+                return Ok(timestamp);
+            }
+        }
+
+        let timestamp = self.sdk.block_timestamp(block_number).await?;
+        self.last_timestamp = Some((block_number, timestamp));
+        Ok(timestamp)
+    }
+}
+
+
+impl FourMemeSdk {
+    /// The `eth_getLogs` filter matching every event `FourMemeEvent` decodes.
+    pub fn events_filter(&self) -> Filter {
+        SubscriptionFilter::default().log_filter(*self.contract.address())
+    }
+
+    pub async fn block_timestamp(&self, block_number: u64) -> Result<u64> {
+        let block = self.provider.get_block_by_number(block_number.into()).await?
+            .ok_or_else(|| FourMemeError::Validation(format!("Unknown block {}", block_number)))?;
+
+        Ok(block.header.timestamp)
+    }
+
+    /// Sets `meta.timestamp` on events that lack it, fetching each block once.
+    pub async fn fill_timestamps(&self, events: &mut [FourMemeEvent]) -> Result<()> {
+        let mut timestamps = HashMap::new();
+
+        for event in events.iter_mut().filter(|e| e.meta.timestamp.is_none()) {
+            let Some(block_number) = event.meta.block_number else {
+                continue;
+            };
+            let timestamp = match timestamps.get(&block_number) {
+                Some(timestamp) => *timestamp,
+                None => {
+                    let timestamp = self.block_timestamp(block_number).await?;
+                    timestamps.insert(block_number, timestamp);
+                    timestamp
+                }
+            };
+            event.meta.timestamp = Some(timestamp);
+        }
+
+        Ok(())
+    }
+
+    /// Subscribes to TokenManager events in a way that survives RPC failures.
+    ///
+    /// Events are fetched with `eth_getLogs` from a block cursor, so after a disconnect the
+    /// missed range is backfilled before new blocks are followed, and no event is lost or
+    /// delivered twice. Failures are sent as `Err` items and retried with backoff; the task
+    /// ends when the receiver is dropped.
+    pub async fn subscribe_events_with(
+        &self,
+        options: SubscriptionOptions,
+    ) -> Result<(tokio::task::JoinHandle<()>, mpsc::Receiver<Result<FourMemeEvent>>)> {
+        let (handle, rx) = self.spawn_follower(options).await?;
+        Ok((handle.into_task(), rx))
+    }
+
+    pub(crate) async fn spawn_follower(
+        &self,
+        options: SubscriptionOptions,
+    ) -> Result<(SubscriptionHandle, mpsc::Receiver<Result<FourMemeEvent>>)> {
+        let next_block = match options.from_block {
+            Some(block) => block,
+            None => self.provider.get_block_number().await? + 1,
+        };
+
+        let (tx, rx) = mpsc::channel(options.channel_capacity);
+        let follower = LogFollower {
+            sdk: self.clone(),
+            pubsub: options.pubsub && self.supports_pubsub(),
+            filter: options.filter.log_filter(*self.contract.address()),
+            options,
+            next_block,
+            last: None,
+            reorged_from: None,
+            last_timestamp: None,
+            blocks: BTreeMap::new(),
+            delivered: BTreeMap::new(),
+        };
+
+        Ok((SubscriptionHandle::spawn(follower.run(tx)), rx))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::U256, sol_types::SolEvent, transports::mock::Asserter};
+
+    use super::*;
+    use crate::test_utils::{mocked_sdk, purchase_log};
+    use crate::IFourMeme;
+
+    fn position(event: &FourMemeEvent) -> (U256, U256) {
+        match &event.event {
+            crate::TokenManagerEvent::TokenPurchase(e) => (e.amount, e.cost),
+            _ => panic!("unexpected event"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resumes_after_failure_without_loss_or_duplicates() {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x6"); // head
+        asserter.push_success(&vec![purchase_log(5, 0), purchase_log(5, 0)]);
+        asserter.push_failure_msg("connection reset");
+        asserter.push_success(&"0x6");
+        asserter.push_success(&vec![purchase_log(6, 0), purchase_log(6, 1)]);
+
+        let sdk = mocked_sdk(asserter).await;
+        let options = SubscriptionOptions::default()
+            .from_block(5)
+            .with_max_block_range(1)
+            .with_poll_interval(Duration::from_millis(1))
+            .with_retry_delay(Duration::from_millis(1));
+
+        let (handle, mut rx) = sdk.subscribe_events_with(options).await.unwrap();
+
+        let first = rx.recv().await.unwrap().unwrap();
+        assert_eq!(position(&first), (U256::from(5u64), U256::ZERO));
+        assert!(rx.recv().await.unwrap().is_err());
+        for index in 0..2u64 {
+            let event = rx.recv().await.unwrap().unwrap();
+            assert_eq!(position(&event), (U256::from(6u64), U256::from(index)));
+        }
+
+        drop(rx);
+        handle.abort();
+    }
+
+    fn block(number: u64, hash: u8) -> alloy::rpc::types::Block {
+        let mut block = alloy::rpc::types::Block::<alloy::rpc::types::Transaction>::default();
+        block.header.hash = B256::repeat_byte(hash);
+        block.header.inner.number = number;
+        block
+    }
+
+    fn in_block(mut log: Log, hash: u8) -> Log {
+        log.block_hash = Some(B256::repeat_byte(hash));
+        log
+    }
+
+    #[tokio::test]
+    async fn test_replaced_blocks_are_reported_removed() {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x6");
+        asserter.push_success(&vec![in_block(purchase_log(5, 0), 0xa5), in_block(purchase_log(6, 0), 0xa6)]);
+        asserter.push_success(&block(6, 0xa6));
+        asserter.push_success(&"0x7");
+        asserter.push_success(&block(6, 0xb6)); // block 6 was replaced
+        asserter.push_success(&block(5, 0xa5)); // fork point
+        asserter.push_success(&vec![in_block(purchase_log(6, 1), 0xb6), in_block(purchase_log(7, 0), 0xb7)]);
+        asserter.push_success(&block(7, 0xb7));
+
+        let sdk = mocked_sdk(asserter).await;
+        let options = SubscriptionOptions::default()
+            .from_block(5)
+            .with_reorg_depth(10)
+            .with_poll_interval(Duration::from_millis(1));
+
+        let (handle, mut rx) = sdk.subscribe_events_with(options).await.unwrap();
+
+        let mut received = Vec::new();
+        for _ in 0..5 {
+            let event = rx.recv().await.unwrap().unwrap();
+            received.push((position(&event), event.meta.removed));
+        }
+        let at = |block: u64, index: u64| (U256::from(block), U256::from(index));
+        assert_eq!(received, vec![
+            (at(5, 0), false),
+            (at(6, 0), false),
+            (at(6, 0), true),
+            (at(6, 1), false),
+            (at(7, 0), false),
+        ]);
+
+        drop(rx);
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_logs_removed_by_the_node_are_forwarded_and_replayed() {
+        let mut removed = purchase_log(5, 1);
+        removed.removed = true;
+
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x5");
+        asserter.push_success(&vec![purchase_log(5, 0), purchase_log(5, 1)]);
+        asserter.push_success(&"0x6");
+        asserter.push_success(&vec![removed.clone(), purchase_log(5, 1), purchase_log(6, 0)]);
+
+        let sdk = mocked_sdk(asserter).await;
+        let options = SubscriptionOptions::default()
+            .from_block(5)
+            .with_poll_interval(Duration::from_millis(1));
+
+        let (handle, mut rx) = sdk.subscribe_events_with(options).await.unwrap();
+
+        let mut received = Vec::new();
+        for _ in 0..5 {
+            let event = rx.recv().await.unwrap().unwrap();
+            received.push((event.meta.position().unwrap().block_number, event.meta.position().unwrap().log_index, event.meta.removed));
+        }
+        assert_eq!(received, vec![(5, 0, false), (5, 1, false), (5, 1, true), (5, 1, false), (6, 0, false)]);
+
+        drop(rx);
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_confirmations_delay_delivery() {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x6"); // only block 4 is confirmed: nothing to fetch yet
+        asserter.push_success(&"0x7");
+        asserter.push_success(&vec![purchase_log(5, 0)]);
+
+        let sdk = mocked_sdk(asserter).await;
+        let options = SubscriptionOptions::default()
+            .from_block(5)
+            .with_confirmations(2)
+            .with_poll_interval(Duration::from_millis(1));
+
+        let (handle, mut rx) = sdk.subscribe_events_with(options).await.unwrap();
+        let event = rx.recv().await.unwrap().unwrap();
+        assert_eq!(position(&event), (U256::from(5u64), U256::ZERO));
+
+        drop(rx);
+        handle.abort();
+    }
+
+    /// Tests against a local node; they need `anvil` on the `PATH` and run with `cargo test -- --ignored`.
+    #[cfg(any(feature = "ws", feature = "ipc"))]
+    mod anvil {
+        use std::process::{Child, Command, Stdio};
+
+        use alloy::{
+            network::TransactionBuilder,
+            primitives::Bytes,
+            rpc::types::TransactionRequest,
+            signers::local::PrivateKeySigner,
+        };
+
+        use super::*;
+
+        /// First of anvil's default dev accounts.
+        const DEV_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+        const CHAIN_ID: u64 = 31337;
+
+        struct Anvil(Child);
+
+        impl Anvil {
+            async fn spawn(args: &[&str]) -> Self {
+                let child = Command::new("anvil")
+                    .args(args)
+                    .stdout(Stdio::null())
+                    .spawn()
+                    .expect("anvil must be installed");
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Self(child)
+            }
+        }
+
+        impl Drop for Anvil {
+            fn drop(&mut self) {
+                let _ = self.0.kill();
+            }
+        }
+
+        /// Deploys a contract that emits its calldata as a `TokenPurchase` log, and points the SDK at it.
+        async fn with_emitter(sdk: FourMemeSdk) -> FourMemeSdk {
+            let runtime = [
+                &[0x36, 0x60, 0x00, 0x60, 0x00, 0x37, 0x7f][..], // calldatacopy(0, 0, calldatasize); push32
+                IFourMeme::TokenPurchase::SIGNATURE_HASH.as_slice(),
+                &[0x36, 0x60, 0x00, 0xa1, 0x00], // log1(0, calldatasize, topic); stop
+            ].concat();
+            let init = [&[0x60, runtime.len() as u8, 0x60, 0x0c, 0x60, 0x00, 0x39, 0x60, runtime.len() as u8, 0x60, 0x00, 0xf3][..], &runtime].concat();
+
+            let receipt = sdk.provider.send_transaction(TransactionRequest::default().with_deploy_code(Bytes::from(init))).await.unwrap()
+                .get_receipt().await.unwrap();
+            let address = receipt.contract_address.unwrap();
+
+            FourMemeSdk::new_with_provider(sdk.provider.clone(), Some(address), None).await.unwrap()
+                .with_signer(sdk.signer.unwrap())
+        }
+
+        async fn emit_purchase(sdk: &FourMemeSdk, amount: u64) {
+            let data = purchase_log(0, amount).inner.data.data;
+            let tx = TransactionRequest::default().to(*sdk.contract.address()).input(data.into());
+            sdk.provider.send_transaction(tx).await.unwrap().get_receipt().await.unwrap();
+        }
+
+        async fn assert_pushed(sdk: FourMemeSdk) {
+            assert!(sdk.supports_pubsub());
+            let sdk = with_emitter(sdk).await;
+
+            let options = SubscriptionOptions::default().with_poll_interval(Duration::from_secs(3600));
+            let (handle, mut rx) = sdk.subscribe_events_with(options).await.unwrap();
+            #[allow(deprecated)]
+            let (legacy, mut legacy_rx) = sdk.subscribe_events().await.unwrap();
+
+            emit_purchase(&sdk, 7).await;
+
+            // With an hour-long poll interval, only the subscription can deliver in time.
+            let event = tokio::time::timeout(Duration::from_secs(10), rx.recv()).await.unwrap().unwrap().unwrap();
+            assert_eq!(position(&event).1, U256::from(7u64));
+            let event = tokio::time::timeout(Duration::from_secs(10), legacy_rx.recv()).await.unwrap().unwrap();
+            assert_eq!(position(&event).1, U256::from(7u64));
+
+            handle.abort();
+            legacy.abort();
+        }
+
+        #[cfg(feature = "ws")]
+        #[tokio::test]
+        #[ignore = "requires anvil"]
+        async fn test_ws_subscription_is_pushed() {
+            let _anvil = Anvil::spawn(&["--port", "18545"]).await;
+            let signer: PrivateKeySigner = DEV_KEY.parse().unwrap();
+            let sdk = FourMemeSdk::new_with_ws("ws://127.0.0.1:18545", signer, CHAIN_ID, None, None).await.unwrap();
+
+            assert_pushed(sdk).await;
+        }
+
+        #[cfg(feature = "ipc")]
+        #[tokio::test]
+        #[ignore = "requires anvil"]
+        async fn test_ipc_subscription_is_pushed() {
+            let path = std::env::temp_dir().join("four-meme-sdk-anvil.ipc");
+            let _anvil = Anvil::spawn(&["--port", "18546", "--ipc", path.to_str().unwrap()]).await;
+            let signer: PrivateKeySigner = DEV_KEY.parse().unwrap();
+            let sdk = FourMemeSdk::new_with_ipc(path, signer, CHAIN_ID, None, None).await.unwrap();
+
+            assert_pushed(sdk).await;
+        }
+    }
+}
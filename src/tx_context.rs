@@ -1,7 +0,0 @@
-
-
-
-pub struct TxContext {
-    pub max_priority_fee_per_gas: u128,
-    pub max_fee_per_gas: u128,
-}
\ No newline at end of file
@@ -1,15 +1,241 @@
-use alloy::primitives::{Address, U256};
+use alloy::{primitives::{Address, TxHash, B256, U256}, rpc::types::Log, sol_types::SolEvent};
 use serde::{Deserialize, Serialize};
 
-use crate::IFourMeme;
+use crate::{IFourMeme, LogPosition};
 
 
 
-#[derive(Clone)]
-pub enum FourMemeEvent {
+/// Where and when a TokenManager event was emitted.
+///
+/// Fields are `None` for pending logs; `timestamp` is only set when the node includes it in
+/// the log or it was fetched with [`FourMemeSdk::fill_timestamps`](crate::FourMemeSdk::fill_timestamps).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventMeta {
+    pub block_number: Option<u64>,
+    pub block_hash: Option<B256>,
+    pub tx_hash: Option<TxHash>,
+    pub log_index: Option<u64>,
+    /// Block timestamp, seconds since the epoch.
+    pub timestamp: Option<u64>,
+    /// The log was reverted by a chain reorganization; undo whatever the event triggered.
+    #[serde(default)]
+    pub removed: bool,
+}
+
+impl EventMeta {
+    pub fn from_log(log: &Log) -> Self {
+        Self {
+            block_number: log.block_number,
+            block_hash: log.block_hash,
+            tx_hash: log.transaction_hash,
+            log_index: log.log_index,
+            timestamp: log.block_timestamp,
+            removed: log.removed,
+        }
+    }
+
+    pub fn position(&self) -> Option<LogPosition> {
+        Some(LogPosition { block_number: self.block_number?, log_index: self.log_index? })
+    }
+}
+
+
+/// The type of a [`TokenManagerEvent`], without its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum EventKind {
+    TokenPurchase,
+    TokenSale,
+    TokenCreate,
+    LiquidityAdded,
+    TradeStop,
+    RoleGranted,
+    RoleRevoked,
+    RoleAdminChanged,
+    OwnershipTransferred,
+    Upgraded,
+    AdminChanged,
+    BeaconUpgraded,
+    Initialized,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 13] = [
+        Self::TokenPurchase,
+        Self::TokenSale,
+        Self::TokenCreate,
+        Self::LiquidityAdded,
+        Self::TradeStop,
+        Self::RoleGranted,
+        Self::RoleRevoked,
+        Self::RoleAdminChanged,
+        Self::OwnershipTransferred,
+        Self::Upgraded,
+        Self::AdminChanged,
+        Self::BeaconUpgraded,
+        Self::Initialized,
+    ];
+
+    /// Topic0 of the event.
+    pub fn signature(self) -> B256 {
+        match self {
+            Self::TokenPurchase => IFourMeme::TokenPurchase::SIGNATURE_HASH,
+            Self::TokenSale => IFourMeme::TokenSale::SIGNATURE_HASH,
+            Self::TokenCreate => IFourMeme::TokenCreate::SIGNATURE_HASH,
+            Self::LiquidityAdded => IFourMeme::LiquidityAdded::SIGNATURE_HASH,
+            Self::TradeStop => IFourMeme::TradeStop::SIGNATURE_HASH,
+            Self::RoleGranted => IFourMeme::RoleGranted::SIGNATURE_HASH,
+            Self::RoleRevoked => IFourMeme::RoleRevoked::SIGNATURE_HASH,
+            Self::RoleAdminChanged => IFourMeme::RoleAdminChanged::SIGNATURE_HASH,
+            Self::OwnershipTransferred => IFourMeme::OwnershipTransferred::SIGNATURE_HASH,
+            Self::Upgraded => IFourMeme::Upgraded::SIGNATURE_HASH,
+            Self::AdminChanged => IFourMeme::AdminChanged::SIGNATURE_HASH,
+            Self::BeaconUpgraded => IFourMeme::BeaconUpgraded::SIGNATURE_HASH,
+            Self::Initialized => IFourMeme::Initialized::SIGNATURE_HASH,
+        }
+    }
+}
+
+
+/// Every event the TokenManager proxy emits.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenManagerEvent {
     TokenPurchase(IFourMeme::TokenPurchase),
     TokenSale(IFourMeme::TokenSale),
     TokenCreate(IFourMeme::TokenCreate),
+    /// The curve completed and liquidity was added to PancakeSwap (graduation).
+    LiquidityAdded(IFourMeme::LiquidityAdded),
+    /// Bonding-curve trading stopped for the token, right before `LiquidityAdded`.
+    TradeStop(IFourMeme::TradeStop),
+    RoleGranted(IFourMeme::RoleGranted),
+    RoleRevoked(IFourMeme::RoleRevoked),
+    RoleAdminChanged(IFourMeme::RoleAdminChanged),
+    OwnershipTransferred(IFourMeme::OwnershipTransferred),
+    /// The proxy implementation changed.
+    Upgraded(IFourMeme::Upgraded),
+    AdminChanged(IFourMeme::AdminChanged),
+    BeaconUpgraded(IFourMeme::BeaconUpgraded),
+    Initialized(IFourMeme::Initialized),
+}
+
+impl TokenManagerEvent {
+    /// Topic0 of every event this enum decodes.
+    pub fn signatures() -> Vec<B256> {
+        EventKind::ALL.iter().map(|kind| kind.signature()).collect()
+    }
+
+    /// Decodes a TokenManager log, returning `None` for logs of other contracts' events.
+    pub fn decode_log(log: &Log) -> Option<Self> {
+        match *log.topic0()? {
+            IFourMeme::TokenPurchase::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::TokenPurchase(l.inner.data)),
+            IFourMeme::TokenSale::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::TokenSale(l.inner.data)),
+            IFourMeme::TokenCreate::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::TokenCreate(l.inner.data)),
+            IFourMeme::LiquidityAdded::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::LiquidityAdded(l.inner.data)),
+            IFourMeme::TradeStop::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::TradeStop(l.inner.data)),
+            IFourMeme::RoleGranted::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::RoleGranted(l.inner.data)),
+            IFourMeme::RoleRevoked::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::RoleRevoked(l.inner.data)),
+            IFourMeme::RoleAdminChanged::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::RoleAdminChanged(l.inner.data)),
+            IFourMeme::OwnershipTransferred::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::OwnershipTransferred(l.inner.data)),
+            IFourMeme::Upgraded::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::Upgraded(l.inner.data)),
+            IFourMeme::AdminChanged::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::AdminChanged(l.inner.data)),
+            IFourMeme::BeaconUpgraded::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::BeaconUpgraded(l.inner.data)),
+            IFourMeme::Initialized::SIGNATURE_HASH => log.log_decode().ok().map(|l| Self::Initialized(l.inner.data)),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::TokenPurchase(_) => EventKind::TokenPurchase,
+            Self::TokenSale(_) => EventKind::TokenSale,
+            Self::TokenCreate(_) => EventKind::TokenCreate,
+            Self::LiquidityAdded(_) => EventKind::LiquidityAdded,
+            Self::TradeStop(_) => EventKind::TradeStop,
+            Self::RoleGranted(_) => EventKind::RoleGranted,
+            Self::RoleRevoked(_) => EventKind::RoleRevoked,
+            Self::RoleAdminChanged(_) => EventKind::RoleAdminChanged,
+            Self::OwnershipTransferred(_) => EventKind::OwnershipTransferred,
+            Self::Upgraded(_) => EventKind::Upgraded,
+            Self::AdminChanged(_) => EventKind::AdminChanged,
+            Self::BeaconUpgraded(_) => EventKind::BeaconUpgraded,
+            Self::Initialized(_) => EventKind::Initialized,
+        }
+    }
+
+    /// The meme token the event is about, if any.
+    pub fn token(&self) -> Option<Address> {
+        match self {
+            Self::TokenPurchase(e) => Some(e.token),
+            Self::TokenSale(e) => Some(e.token),
+            Self::TokenCreate(e) => Some(e.token),
+            Self::LiquidityAdded(e) => Some(e.base),
+            Self::TradeStop(e) => Some(e.token),
+            _ => None,
+        }
+    }
+
+    /// The buyer, seller or creator.
+    pub fn trader(&self) -> Option<Address> {
+        match self {
+            Self::TokenPurchase(e) => Some(e.account),
+            Self::TokenSale(e) => Some(e.account),
+            Self::TokenCreate(e) => Some(e.creator),
+            _ => None,
+        }
+    }
+
+    /// BNB moved by the event: the trade cost, or the funds added as liquidity.
+    pub fn funds(&self) -> Option<U256> {
+        match self {
+            Self::TokenPurchase(e) => Some(e.cost),
+            Self::TokenSale(e) => Some(e.cost),
+            Self::LiquidityAdded(e) => Some(e.funds),
+            _ => None,
+        }
+    }
+
+    /// The token a graduation (`TradeStop` / `LiquidityAdded`) refers to.
+    pub fn graduated_token(&self) -> Option<Address> {
+        match self {
+            Self::TradeStop(e) => Some(e.token),
+            Self::LiquidityAdded(e) => Some(e.base),
+            _ => None,
+        }
+    }
+
+    /// Access control, ownership, proxy upgrade and initialization events.
+    pub fn is_governance(&self) -> bool {
+        matches!(
+            self,
+            Self::RoleGranted(_)
+                | Self::RoleRevoked(_)
+                | Self::RoleAdminChanged(_)
+                | Self::OwnershipTransferred(_)
+                | Self::Upgraded(_)
+                | Self::AdminChanged(_)
+                | Self::BeaconUpgraded(_)
+                | Self::Initialized(_)
+        )
+    }
+}
+
+
+/// A decoded TokenManager event together with the log position it came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FourMemeEvent {
+    pub meta: EventMeta,
+    pub event: TokenManagerEvent,
+}
+
+impl FourMemeEvent {
+    /// Topic0 of every event this type decodes.
+    pub fn signatures() -> Vec<B256> {
+        TokenManagerEvent::signatures()
+    }
+
+    /// Decodes a TokenManager log, returning `None` for logs of other contracts' events.
+    pub fn decode_log(log: &Log) -> Option<Self> {
+        Some(Self { meta: EventMeta::from_log(log), event: TokenManagerEvent::decode_log(log)? })
+    }
 }
 
 
@@ -30,7 +256,7 @@ pub struct BuyAmapParams {
     pub to: Option<Address>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SellAmapParams {
     pub token: Address,
     pub amount: U256,
@@ -190,4 +416,73 @@ pub struct TokenPrice {
     pub modify_date: String,
     pub bamount: String,
     pub tamount: String,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_carries_log_meta_and_serializes() {
+        let purchase = IFourMeme::TokenPurchase {
+            token: Address::repeat_byte(0x44),
+            account: Address::repeat_byte(0xaa),
+            price: U256::from(1u64),
+            amount: U256::from(2u64),
+            cost: U256::from(3u64),
+            fee: U256::ZERO,
+            offers: U256::ZERO,
+            funds: U256::ZERO,
+        };
+        let log = Log {
+            inner: alloy::primitives::Log { address: Address::ZERO, data: purchase.encode_log_data() },
+            block_hash: Some(B256::repeat_byte(1)),
+            block_number: Some(42),
+            block_timestamp: Some(1_700_000_000),
+            transaction_hash: Some(TxHash::repeat_byte(2)),
+            log_index: Some(7),
+            ..Default::default()
+        };
+
+        let event = FourMemeEvent::decode_log(&log).unwrap();
+        assert_eq!(event.meta, EventMeta {
+            block_number: Some(42),
+            block_hash: Some(B256::repeat_byte(1)),
+            tx_hash: Some(TxHash::repeat_byte(2)),
+            log_index: Some(7),
+            timestamp: Some(1_700_000_000),
+            removed: false,
+        });
+        assert_eq!(event.event, TokenManagerEvent::TokenPurchase(purchase));
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<FourMemeEvent>(&json).unwrap(), event);
+        assert!(format!("{:?}", event).contains("TokenPurchase"));
+    }
+
+    #[test]
+    fn test_graduation_and_governance_events_decode() {
+        let token = Address::repeat_byte(0x44);
+        let log = |data: alloy::primitives::LogData| Log {
+            inner: alloy::primitives::Log { address: Address::ZERO, data },
+            ..Default::default()
+        };
+
+        let stop = TokenManagerEvent::decode_log(&log(IFourMeme::TradeStop { token }.encode_log_data())).unwrap();
+        assert_eq!(stop.graduated_token(), Some(token));
+
+        let added = IFourMeme::LiquidityAdded { base: token, offers: U256::from(1u64), quote: Address::ZERO, funds: U256::from(2u64) };
+        let added = TokenManagerEvent::decode_log(&log(added.encode_log_data())).unwrap();
+        assert_eq!(added.graduated_token(), Some(token));
+        assert!(!added.is_governance());
+
+        let upgraded = IFourMeme::Upgraded { implementation: Address::repeat_byte(0x11) };
+        let upgraded = TokenManagerEvent::decode_log(&log(upgraded.encode_log_data())).unwrap();
+        assert_eq!(upgraded, TokenManagerEvent::Upgraded(IFourMeme::Upgraded { implementation: Address::repeat_byte(0x11) }));
+        assert!(upgraded.is_governance());
+
+        let granted = IFourMeme::RoleGranted { role: B256::repeat_byte(1), account: token, sender: Address::ZERO };
+        assert!(matches!(TokenManagerEvent::decode_log(&log(granted.encode_log_data())), Some(TokenManagerEvent::RoleGranted(_))));
+        assert_eq!(TokenManagerEvent::signatures().len(), 13);
+    }
+}